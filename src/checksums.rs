@@ -0,0 +1,324 @@
+use std::sync::LazyLock;
+
+use camino::Utf8Path;
+use color_eyre::eyre::{Result, bail};
+use futures_util::{StreamExt, stream};
+use itertools::Itertools;
+use regex::Regex;
+use reqwest::Client;
+use tracing::{debug, error, info, warn};
+use url::Url;
+use winget_types::shared::Sha256String;
+
+use crate::{download_file::DownloadedFile, github::graphql::get_all_values::ReleaseAsset};
+
+/// Checksum files that list the hashes of every asset in a release
+const CHECKSUM_FILE_NAMES: [&str; 6] = [
+    "sha256sums",
+    "sha256sums.txt",
+    "sha256sum.txt",
+    "sha256.txt",
+    "checksums.txt",
+    "checksums.sha256",
+];
+
+/// Extensions of checksum files that contain the hash of the file they are named after
+const CHECKSUM_EXTENSIONS: [&str; 2] = ["sha256", "sha256sum"];
+
+/// A hash listed in a checksum file, with the name of the file it belongs to if the checksum file
+/// lists more than one.
+#[derive(Debug, Eq, PartialEq)]
+struct Checksum {
+    file_name: Option<String>,
+    sha_256: Sha256String,
+}
+
+/// A checksum file and the hashes listed in it.
+struct ChecksumFile {
+    url: Url,
+    checksums: Vec<Checksum>,
+    /// Whether the file was only guessed from naming conventions rather than published with the
+    /// release, in which case it may belong to a different file of the same name
+    speculative: bool,
+}
+
+impl ChecksumFile {
+    /// Returns the hash listed for a file name.
+    ///
+    /// Speculative checksum files only count if they list the file by its exact name, as a
+    /// `SHA256SUMS` next to the installer may describe another build in a subdirectory.
+    fn find(&self, file_name: &str) -> Option<&Sha256String> {
+        let checksum_file_name = last_segment(&self.url)?;
+        self.checksums
+            .iter()
+            .find(|checksum| match &checksum.file_name {
+                Some(name) if self.speculative => name.trim_start_matches("./") == file_name,
+                Some(name) => Utf8Path::new(name).file_name() == Some(file_name),
+                None => Utf8Path::new(checksum_file_name).file_stem() == Some(file_name),
+            })
+            .map(|checksum| &checksum.sha_256)
+    }
+}
+
+fn is_checksum_file(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    CHECKSUM_FILE_NAMES.contains(&name.as_str())
+        || (name.contains("checksums") && name.ends_with(".txt"))
+        || Utf8Path::new(&name)
+            .extension()
+            .is_some_and(|extension| CHECKSUM_EXTENSIONS.contains(&extension))
+}
+
+/// Parses the output of `sha256sum` in both GNU and BSD formats, as well as files that only
+/// contain a hash.
+fn parse_checksums(text: &str) -> Vec<Checksum> {
+    static GNU_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^([0-9A-Fa-f]{64})\s+\*?(.+)$").unwrap());
+    static BSD_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^SHA256 \((.+)\) = ([0-9A-Fa-f]{64})$").unwrap());
+    static HASH_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^([0-9A-Fa-f]{64})$").unwrap());
+
+    text.lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            let (sha_256, file_name) = if let Some(captures) = GNU_REGEX.captures(line) {
+                (captures.get(1)?, captures.get(2))
+            } else if let Some(captures) = BSD_REGEX.captures(line) {
+                (captures.get(2)?, captures.get(1))
+            } else {
+                (HASH_REGEX.captures(line)?.get(1)?, None)
+            };
+            Some(Checksum {
+                file_name: file_name.map(|file_name| file_name.as_str().trim().to_owned()),
+                sha_256: sha_256.as_str().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+fn last_segment(url: &Url) -> Option<&str> {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+}
+
+/// Returns the checksum files that could be published alongside an installer that is not a
+/// release asset, such as `setup.exe.sha256` or a `SHA256SUMS` in the same directory.
+fn conventional_checksum_urls(url: &Url) -> Vec<Url> {
+    const SIBLING_FILE_NAMES: [&str; 2] = ["SHA256SUMS", "checksums.txt"];
+
+    let Some(file_name) = last_segment(url) else {
+        return Vec::new();
+    };
+    [format!("{file_name}.{}", CHECKSUM_EXTENSIONS[0])]
+        .into_iter()
+        .chain(SIBLING_FILE_NAMES.map(str::to_owned))
+        .filter_map(|name| url.join(&name).ok())
+        .collect()
+}
+
+async fn fetch_checksums(client: &Client, url: &Url) -> Option<Vec<Checksum>> {
+    const MAX_CHECKSUM_FILE_SIZE: usize = 1 << 20;
+
+    let response = client
+        .get(url.as_str())
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .ok()?;
+    if response
+        .content_length()
+        .is_some_and(|content_length| content_length > MAX_CHECKSUM_FILE_SIZE as u64)
+    {
+        return None;
+    }
+
+    // Responses without a Content-Length can only be checked as they stream in
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(&chunk.ok()?);
+        if body.len() > MAX_CHECKSUM_FILE_SIZE {
+            debug!(%url, "Checksum file is too large");
+            return None;
+        }
+    }
+
+    let checksums = parse_checksums(&String::from_utf8_lossy(&body));
+    (!checksums.is_empty()).then_some(checksums)
+}
+
+/// Compares the hash of each downloaded file against the checksum files published with it,
+/// failing if any of them differ, as that means the download was truncated or tampered with.
+///
+/// Checksum files are taken from the GitHub release's assets, or otherwise looked for next to the
+/// installer by common naming conventions. As those are only guessed, a mismatch with them is
+/// warned about instead.
+pub async fn verify_checksums(
+    client: &Client,
+    files: &[DownloadedFile],
+    release_assets: &[ReleaseAsset],
+) -> Result<()> {
+    const CONCURRENT_REQUESTS: usize = 4;
+
+    let checksum_urls = release_assets
+        .iter()
+        .filter(|asset| is_checksum_file(&asset.name))
+        .map(|asset| (asset.download_url.clone(), false))
+        .chain(
+            files
+                .iter()
+                .filter(|file| {
+                    !release_assets
+                        .iter()
+                        .any(|asset| asset.download_url == **file.url())
+                })
+                .flat_map(|file| conventional_checksum_urls(file.url()))
+                .map(|url| (url, true)),
+        )
+        .unique_by(|(url, _)| url.clone())
+        .collect::<Vec<_>>();
+
+    let mut checksum_files = stream::iter(checksum_urls)
+        .map(|(url, speculative)| async move {
+            let checksums = fetch_checksums(client, &url).await?;
+            Some(ChecksumFile {
+                url,
+                checksums,
+                speculative,
+            })
+        })
+        .buffer_unordered(CONCURRENT_REQUESTS)
+        .filter_map(|checksum_file| async move { checksum_file })
+        .collect::<Vec<_>>()
+        .await;
+
+    // Published checksum files take precedence over ones found by convention
+    checksum_files.sort_by_key(|checksum_file| checksum_file.speculative);
+
+    let mut mismatches = 0;
+    for file in files {
+        let Some(file_name) = last_segment(file.url()) else {
+            continue;
+        };
+        let expected = checksum_files.iter().find_map(|checksum_file| {
+            checksum_file
+                .find(file_name)
+                .map(|sha_256| (checksum_file, sha_256))
+        });
+
+        match expected {
+            Some((checksum_file, sha_256)) if sha_256 == file.sha_256() => {
+                info!("{} 的 SHA-256 与 {} 一致", file.url(), checksum_file.url);
+            }
+            Some((checksum_file, sha_256)) if checksum_file.speculative => {
+                warn!(
+                    "{} 的 SHA-256 为 {}，但 {} 中为 {sha_256}。该校验和文件是按惯例推测的，可能不属于此安装程序",
+                    file.url(),
+                    file.sha_256(),
+                    checksum_file.url
+                );
+            }
+            Some((checksum_file, sha_256)) => {
+                error!(
+                    "{} 的 SHA-256 为 {}，但 {} 中为 {sha_256}",
+                    file.url(),
+                    file.sha_256(),
+                    checksum_file.url
+                );
+                mismatches += 1;
+            }
+            None => debug!(url = %file.url(), "No published checksum found"),
+        }
+    }
+
+    if mismatches > 0 {
+        bail!("{mismatches} 个安装程序与发布的校验和不一致，下载可能不完整或已被篡改");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rstest::rstest;
+    use winget_types::shared::Sha256String;
+
+    use crate::checksums::{ChecksumFile, is_checksum_file, parse_checksums};
+
+    const HASH: &str = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+
+    #[rstest]
+    #[case("SHA256SUMS", true)]
+    #[case("checksums.txt", true)]
+    #[case("app_1.2.3_checksums.txt", true)]
+    #[case("setup.exe.sha256", true)]
+    #[case("setup.exe", false)]
+    #[case("release-notes.txt", false)]
+    fn checksum_file(#[case] name: &str, #[case] expected: bool) {
+        assert_eq!(is_checksum_file(name), expected);
+    }
+
+    #[test]
+    fn gnu_format() {
+        let text = indoc! {"
+            e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  setup-x64.exe
+            e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 *dist/setup-x86.exe
+        "};
+        let checksums = parse_checksums(text);
+        assert_eq!(
+            checksums
+                .iter()
+                .map(|checksum| checksum.file_name.as_deref())
+                .collect::<Vec<_>>(),
+            [Some("setup-x64.exe"), Some("dist/setup-x86.exe")]
+        );
+        assert!(
+            checksums
+                .iter()
+                .all(|checksum| checksum.sha_256 == HASH.parse::<Sha256String>().unwrap())
+        );
+    }
+
+    #[test]
+    fn bsd_format() {
+        let checksums = parse_checksums(&format!("SHA256 (setup.exe) = {HASH}"));
+        assert_eq!(checksums.len(), 1);
+        assert_eq!(checksums[0].file_name.as_deref(), Some("setup.exe"));
+    }
+
+    #[test]
+    fn hash_only() {
+        let checksums = parse_checksums(&format!("{HASH}\n"));
+        assert_eq!(checksums.len(), 1);
+        assert_eq!(checksums[0].file_name, None);
+    }
+
+    #[rstest]
+    #[case::published("setup.exe", false, true)]
+    #[case::published_in_directory("dist/setup.exe", false, true)]
+    #[case::speculative("setup.exe", true, true)]
+    #[case::speculative_relative("./setup.exe", true, true)]
+    #[case::speculative_in_directory("dist/setup.exe", true, false)]
+    fn find_checksum(#[case] listed_name: &str, #[case] speculative: bool, #[case] found: bool) {
+        let checksum_file = ChecksumFile {
+            url: "https://example.com/SHA256SUMS".parse().unwrap(),
+            checksums: parse_checksums(&format!("{HASH}  {listed_name}")),
+            speculative,
+        };
+        assert_eq!(checksum_file.find("setup.exe").is_some(), found);
+    }
+
+    #[test]
+    fn find_checksum_named_after_file() {
+        let checksum_file = ChecksumFile {
+            url: "https://example.com/setup.exe.sha256".parse().unwrap(),
+            checksums: parse_checksums(HASH),
+            speculative: true,
+        };
+        assert!(checksum_file.find("setup.exe").is_some());
+        assert!(checksum_file.find("setup.msi").is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use std::fs::File;
+use std::{fmt::Write, fs::File};
 
 use anstream::stdout;
 use camino::{Utf8Path, Utf8PathBuf};
@@ -46,16 +46,42 @@ impl Analyse {
             .file_name()
             .unwrap_or_else(|| self.file_path.as_str());
         let mut analyser = FileAnalyser::new(&mmap, file_name)?;
+        if let Some(release_date) = analyser.release_date {
+            for installer in &mut analyser.installers {
+                installer.release_date = Some(release_date.date);
+            }
+        }
         if self.hash {
             let sha_256 = Sha256String::from_hasher(&Sha256::digest(&mmap))?;
             for installer in &mut analyser.installers {
                 installer.sha_256 = sha_256.clone();
             }
         }
-        let yaml = match analyser.installers.as_slice() {
+        let mut yaml = String::new();
+        if let Some(signer) = &analyser.signer {
+            let _ = writeln!(yaml, "# 签名者: {}", signer.subject);
+            let _ = writeln!(yaml, "# 颁发者: {}", signer.issuer);
+            if let (Some(not_before), Some(not_after)) = (signer.not_before, signer.not_after) {
+                let _ = writeln!(yaml, "# 有效期: {not_before} - {not_after}");
+            }
+            if !signer.chain.is_empty() {
+                let _ = writeln!(yaml, "# 证书链: {}", signer.chain.join(" -> "));
+            }
+        }
+        if let Some(release_date) = analyser.release_date {
+            let _ = writeln!(yaml, "# 发布日期: {release_date}");
+        }
+        if let Some(update_settings) = analyser
+            .app_installer
+            .as_ref()
+            .and_then(|app_installer| app_installer.update_settings.as_ref())
+        {
+            let _ = writeln!(yaml, "# 更新设置: {update_settings}");
+        }
+        yaml.push_str(&match analyser.installers.as_slice() {
             [installer] => serde_yaml::to_string(installer)?,
             installers => serde_yaml::to_string(installers)?,
-        };
+        });
         let mut lock = stdout().lock();
         print_manifest(&mut lock, &yaml);
         Ok(())
@@ -0,0 +1,14 @@
+use clap::{Args, Subcommand};
+
+use crate::commands::cache::{list::ListCache, prune::PruneCache};
+
+#[derive(Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommands,
+}
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    List(ListCache),
+    Prune(PruneCache),
+}
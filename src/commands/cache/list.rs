@@ -0,0 +1,42 @@
+use anstream::println;
+use clap::Parser;
+use color_eyre::eyre::{Result, eyre};
+use indicatif::DecimalBytes;
+use owo_colors::OwoColorize;
+
+use crate::download_cache::DownloadCache;
+
+/// List the installers in the download cache
+#[derive(Parser)]
+#[clap(visible_alias = "ls")]
+pub struct ListCache;
+
+impl ListCache {
+    pub fn run(self) -> Result<()> {
+        let cache = DownloadCache::open().ok_or_else(|| eyre!("无法打开下载缓存"))?;
+        let entries = cache.entries()?;
+
+        if entries.is_empty() {
+            println!("下载缓存为空");
+            return Ok(());
+        }
+
+        for entry in &entries {
+            println!(
+                "{} {} {}",
+                entry.last_used.format("%Y-%m-%d").blue(),
+                DecimalBytes(entry.size).green(),
+                entry.url
+            );
+        }
+
+        println!(
+            "{} 个文件，共 {}，位于 {}",
+            entries.len(),
+            DecimalBytes(entries.iter().map(|entry| entry.size).sum()),
+            cache.directory()
+        );
+
+        Ok(())
+    }
+}
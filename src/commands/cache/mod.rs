@@ -0,0 +1,3 @@
+pub mod commands;
+pub mod list;
+pub mod prune;
@@ -0,0 +1,52 @@
+use anstream::println;
+use chrono::{TimeDelta, Utc};
+use clap::Parser;
+use color_eyre::eyre::{Result, eyre};
+use indicatif::DecimalBytes;
+use owo_colors::OwoColorize;
+
+use crate::download_cache::DownloadCache;
+
+/// Remove installers from the download cache
+#[derive(Parser)]
+#[clap(visible_alias = "clean")]
+pub struct PruneCache {
+    /// 删除超过此天数未使用的安装程序
+    #[arg(long, default_value_t = 30, conflicts_with = "all")]
+    older_than: u32,
+
+    /// 删除所有缓存的安装程序
+    #[arg(long)]
+    all: bool,
+}
+
+impl PruneCache {
+    pub fn run(self) -> Result<()> {
+        let cache = DownloadCache::open().ok_or_else(|| eyre!("无法打开下载缓存"))?;
+        let cutoff = Utc::now() - TimeDelta::days(i64::from(self.older_than));
+
+        let mut removed = 0;
+        let mut freed = 0;
+        for entry in cache
+            .entries()?
+            .into_iter()
+            .filter(|entry| self.all || entry.last_used < cutoff)
+        {
+            cache.remove(&entry)?;
+            removed += 1;
+            freed += entry.size;
+        }
+
+        if removed == 0 {
+            println!("{}", "没有需要删除的缓存文件".cyan());
+        } else {
+            println!(
+                "{} 删除了 {removed} 个缓存文件，释放了 {}",
+                "成功".green(),
+                DecimalBytes(freed)
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use std::num::NonZeroU8;
+
+use anstream::println;
+use clap::Parser;
+use color_eyre::eyre::{Result, bail};
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use winget_types::{
+    installer::Installer,
+    shared::{PackageIdentifier, PackageVersion},
+};
+
+use crate::{
+    download_file::{DownloadOptions, download_urls},
+    hash_drift::{HashDrift, find_hash_drift, is_same_url},
+    network,
+    repository::{ManifestRepository, RepositoryArgs},
+};
+
+/// 重新下载包的安装程序，检查其 SHA-256 是否与清单一致
+#[derive(Parser)]
+pub struct CheckUrls {
+    /// 包的唯一标识符
+    #[arg()]
+    package_identifier: PackageIdentifier,
+
+    /// 要检查的版本，默认为最新版本
+    #[arg(short = 'v', long = "version")]
+    package_version: Option<PackageVersion>,
+
+    /// 同时下载的安装程序数量
+    #[arg(long, default_value_t = NonZeroU8::new(2).unwrap())]
+    concurrent_downloads: NonZeroU8,
+
+    /// 下载失败时的重试次数
+    #[arg(long, default_value_t = 3)]
+    retries: u8,
+
+    /// 允许下载的最大安装程序大小（以 MB 为单位）
+    #[arg(long, default_value_t = 4096)]
+    max_download_size: u64,
+
+    #[command(flatten)]
+    repository: RepositoryArgs,
+
+    /// 具有 `public_repo` 范围的 GitHub 个人访问令牌
+    #[arg(short, long, env = "GITHUB_TOKEN")]
+    token: Option<String>,
+}
+
+impl CheckUrls {
+    pub async fn run(self) -> Result<()> {
+        let repository = self.repository.open(self.token.as_deref()).await?;
+        let client = network::client(None)?;
+
+        let version = match self.package_version {
+            Some(version) => version,
+            None => repository
+                .get_versions(&self.package_identifier)
+                .await?
+                .pop_last()
+                .unwrap_or_else(|| unreachable!()),
+        };
+        let manifests = repository
+            .get_manifests(&self.package_identifier, &version)
+            .await?;
+        let installers = manifests.installer.installers;
+
+        // The cache is skipped so that every installer is downloaded again from the server, and
+        // only the hashes are compared so nothing needs to be analysed
+        let downloaded_files = download_urls(
+            &client,
+            installers
+                .iter()
+                .map(|installer| installer.url.clone())
+                .collect(),
+            self.concurrent_downloads,
+            DownloadOptions {
+                cache: None,
+                retries: self.retries,
+                max_size: self.max_download_size.saturating_mul(1_000_000),
+                max_analysis_size: 0,
+                // The URLs are already published, so they are checked as they are
+                allow_vanity_url: true,
+            },
+        )
+        .await?;
+
+        // The files are in the same order as the unique URLs they were downloaded from, so each
+        // one is recorded under its published URL. Vanity URLs are resolved to a different URL
+        // when downloaded, so they are reported separately, but an upgrade from HTTP to HTTPS is
+        // not a change in what the URL points to. The upgrade is only made when the host serves
+        // HTTPS, so the URL that was actually fetched is what is compared.
+        let mut current_installers = Vec::new();
+        let mut redirected = Vec::new();
+        for (url, file) in installers
+            .iter()
+            .map(|installer| &installer.url)
+            .unique()
+            .zip(&downloaded_files)
+        {
+            if is_same_url(url, file.url()) {
+                current_installers.push(Installer {
+                    url: url.clone(),
+                    sha_256: file.sha_256().clone(),
+                    ..Installer::default()
+                });
+            } else {
+                redirected.push(url);
+            }
+        }
+
+        let hash_drift = find_hash_drift(&installers, &current_installers);
+
+        for url in installers.iter().map(|installer| &installer.url).unique() {
+            if redirected.contains(&url) {
+                println!("{} {url} 指向最新版本，而不是固定的版本", "不固定".yellow());
+            } else if let Some(HashDrift {
+                previous_sha_256,
+                new_sha_256,
+                ..
+            }) = hash_drift.iter().find(|hash_drift| hash_drift.url == *url)
+            {
+                println!(
+                    "{} {url} 的 SHA-256 从 {previous_sha_256} 变为 {new_sha_256}",
+                    "已变化".red()
+                );
+            } else {
+                println!("{} {url}", "一致".green());
+            }
+        }
+
+        if !hash_drift.is_empty() || !redirected.is_empty() {
+            bail!(
+                "{} {version} 有 {} 个安装程序 URL 的内容会随时间变化",
+                self.package_identifier,
+                hash_drift.len() + redirected.len()
+            );
+        }
+
+        Ok(())
+    }
+}
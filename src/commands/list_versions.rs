@@ -4,7 +4,7 @@ use clap::{Args, Parser};
 use color_eyre::Result;
 use winget_types::shared::PackageIdentifier;
 
-use crate::{credential::handle_token, github::github_client::GitHub};
+use crate::repository::{ManifestRepository, RepositoryArgs};
 
 /// 列出给定包的所有版本
 #[derive(Parser)]
@@ -16,6 +16,9 @@ pub struct ListVersions {
     #[command(flatten)]
     output_type: OutputType,
 
+    #[command(flatten)]
+    repository: RepositoryArgs,
+
     /// 具有 `public_repo` 范围的 GitHub 个人访问令牌
     #[arg(short, long, env = "GITHUB_TOKEN")]
     token: Option<String>,
@@ -39,10 +42,9 @@ struct OutputType {
 
 impl ListVersions {
     pub async fn run(self) -> Result<()> {
-        let token = handle_token(self.token.as_deref()).await?;
-        let github = GitHub::new(&token)?;
+        let repository = self.repository.open(self.token.as_deref()).await?;
 
-        let versions = github.get_versions(&self.package_identifier).await?;
+        let versions = repository.get_versions(&self.package_identifier).await?;
 
         let mut stdout_lock = io::stdout().lock();
         match (
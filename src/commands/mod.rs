@@ -1,4 +1,6 @@
 pub mod analyse;
+pub mod cache;
+pub mod check_urls;
 pub mod cleanup;
 pub mod complete;
 pub mod list_versions;
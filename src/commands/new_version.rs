@@ -12,7 +12,6 @@ use indicatif::ProgressBar;
 use inquire::CustomType;
 use ordinal_trait::Ordinal;
 use owo_colors::OwoColorize;
-use reqwest::Client;
 use winget_types::{
     installer::{
         Command, FileExtension, InstallModes, InstallerManifest, InstallerSuccessCode,
@@ -34,19 +33,23 @@ use winget_types::{
 };
 
 use crate::{
+    checksums::verify_checksums,
     commands::utils::{
         SPINNER_TICK_RATE, SubmitOption, prompt_existing_pull_request, prompt_submit_option,
         write_changes_to_dir,
     },
-    credential::{get_default_headers, handle_token},
-    download_file::{download_urls, process_files},
+    credential::handle_token,
+    download_file::{DownloadArgs, LocalFiles, process_files},
+    forge::get_release_values,
     github::{
-        github_client::{GITHUB_HOST, GitHub, WINGET_PKGS_FULL_NAME},
+        github_client::GitHub,
+        upstream::upstream,
         utils::{get_package_path, pull_request::pr_changes},
     },
     manifests::Manifests,
+    network,
     prompts::{
-        check_prompt, handle_inquire_error,
+        check_prompt, handle_inquire_error, installer_type_prompt,
         list::list_prompt,
         radio_prompt,
         text::{confirm_prompt, optional_prompt, required_prompt},
@@ -117,6 +120,12 @@ pub struct NewVersion {
     #[arg(long, default_value_t = NonZeroU8::new(2).unwrap())]
     concurrent_downloads: NonZeroU8,
 
+    #[command(flatten)]
+    download: DownloadArgs,
+
+    #[command(flatten)]
+    local_files: LocalFiles,
+
     /// 添加此包或版本将解决的问题列表
     #[arg(long)]
     resolves: Option<Vec<NonZeroU32>>,
@@ -149,6 +158,10 @@ pub struct NewVersion {
     #[arg(long, env)]
     skip_pr_check: bool,
 
+    /// 将清单推送到同一版本已打开且来自你的分叉的拉取请求，而不是创建新的拉取请求
+    #[arg(long, conflicts_with = "skip_pr_check")]
+    amend_existing: bool,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN")]
     token: Option<String>,
@@ -158,9 +171,7 @@ impl NewVersion {
     pub async fn run(self) -> Result<()> {
         let token = handle_token(self.token.as_deref()).await?;
         let github = GitHub::new(&token)?;
-        let client = Client::builder()
-            .default_headers(get_default_headers(None))
-            .build()?;
+        let client = network::client(None)?;
 
         let package_identifier = required_prompt(self.package_identifier)?;
 
@@ -177,10 +188,21 @@ impl NewVersion {
 
         let package_version = required_prompt(self.package_version)?;
 
-        if let Some(pull_request) = github
+        let existing_pr = github
             .get_existing_pull_request(&package_identifier, &package_version)
-            .await?
-        {
+            .await?;
+        let amend = match &existing_pr {
+            Some(pull_request) if self.amend_existing => github.can_amend(pull_request).await?,
+            _ => false,
+        };
+        let (amended_pr, existing_pr) = match existing_pr {
+            Some(pull_request) if amend => {
+                println!("将更新现有的拉取请求 {}", pull_request.url.blue());
+                (Some(pull_request), None)
+            }
+            existing_pr => (None, existing_pr),
+        };
+        if let Some(pull_request) = existing_pr {
             if !(self.skip_pr_check || self.dry_run)
                 && !prompt_existing_pull_request(
                     &package_identifier,
@@ -214,22 +236,42 @@ impl NewVersion {
             }
         }
 
-        let github_values = urls
-            .iter()
-            .find(|url| url.host_str() == Some(GITHUB_HOST))
-            .and_then(|url| github.get_all_values_from_url(url));
+        let github_values = get_release_values(&urls, Some(&github), &client);
 
-        let mut files = download_urls(&client, urls, self.concurrent_downloads).await?;
+        let cache = self.download.cache();
+        let download_options = self.download.options(cache.as_ref());
+        let mut files = self
+            .local_files
+            .fetch(&client, urls, self.concurrent_downloads, download_options)
+            .await?;
+        let mut github_values = match github_values {
+            Some(future) => future.await?,
+            None => None,
+        };
+        verify_checksums(
+            &client,
+            &files,
+            github_values
+                .as_ref()
+                .map(|github_values| github_values.release_assets.as_slice())
+                .unwrap_or_default(),
+        )
+        .await?;
         let mut download_results = process_files(&mut files).await?;
 
         let mut installers = Vec::new();
         for analyser in &mut download_results.values_mut() {
+            // Files over the analysis threshold are only hashed, so EXEs have no type yet
+            for installer in &mut analyser.installers {
+                if installer.r#type.is_none() {
+                    installer.r#type = Some(installer_type_prompt(&analyser.file_name)?);
+                }
+            }
             let mut installer_switches = InstallerSwitches::default();
-            if analyser
-                .installers
-                .iter()
-                .any(|installer| installer.r#type == Some(InstallerType::Exe))
-            {
+            // Installers from a known framework already have their silent switches
+            if analyser.installers.iter().any(|installer| {
+                installer.r#type == Some(InstallerType::Exe) && installer.switches.is_none()
+            }) {
                 if confirm_prompt(&format!("{} 是一个便携式应用程序吗?", analyser.file_name))? {
                     for installer in &mut analyser.installers {
                         installer.r#type = Some(InstallerType::Portable);
@@ -247,7 +289,10 @@ impl NewVersion {
                 installer_switches.custom = optional_prompt::<CustomSwitch>(None)?;
             }
             if let Some(zip) = &mut analyser.zip {
-                zip.prompt()?;
+                zip.prompt(&package_identifier)?;
+                if !zip.installers.is_empty() {
+                    analyser.installers = mem::take(&mut zip.installers);
+                }
             }
             let mut analyser_installers = mem::take(&mut analyser.installers);
             for installer in &mut analyser_installers {
@@ -290,10 +335,6 @@ impl NewVersion {
             ..InstallerManifest::default()
         };
 
-        let mut github_values = match github_values {
-            Some(future) => Some(future.await?),
-            None => None,
-        };
         let default_locale_manifest = DefaultLocaleManifest {
             package_identifier: package_identifier.clone(),
             package_version: package_version.clone(),
@@ -404,6 +445,34 @@ impl NewVersion {
             return Ok(());
         }
 
+        if let Some(pull_request) = amended_pr {
+            let pr_progress = ProgressBar::new_spinner().with_message(format!(
+                "正在为 {package_identifier} {package_version} 更新拉取请求"
+            ));
+            pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
+
+            let pull_request_url = github
+                .amend_pull_request()
+                .identifier(&package_identifier)
+                .version(&package_version)
+                .maybe_versions(versions.as_ref())
+                .pull_request(pull_request)
+                .changes(changes)
+                .send()
+                .await?;
+
+            pr_progress.finish_and_clear();
+
+            println!("{} 更新了现有的拉取请求", "成功".green());
+            println!("{}", pull_request_url.as_str());
+
+            if self.open_pr {
+                open::that(pull_request_url.as_str())?;
+            }
+
+            return Ok(());
+        }
+
         // 创建一个不确定的进度条，以显示正在创建拉取请求
         let pr_progress = ProgressBar::new_spinner().with_message(format!(
             "正在为 {package_identifier} {package_version} 创建拉取请求"
@@ -424,10 +493,7 @@ impl NewVersion {
 
         pr_progress.finish_and_clear();
 
-        println!(
-            "{} 创建了一个向 {WINGET_PKGS_FULL_NAME} 的拉取请求",
-            "成功".green()
-        );
+        println!("{} 创建了一个向 {} 的拉取请求", "成功".green(), upstream());
         println!("{}", pull_request_url.as_str());
 
         if self.open_pr {
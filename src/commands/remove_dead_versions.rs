@@ -14,7 +14,7 @@ use futures_util::{StreamExt, TryStreamExt, stream};
 use indicatif::ProgressBar;
 use itertools::Itertools;
 use owo_colors::OwoColorize;
-use reqwest::{Client, StatusCode};
+use reqwest::StatusCode;
 use tokio::time::sleep;
 use winget_types::{
     installer::InstallerManifest,
@@ -23,8 +23,9 @@ use winget_types::{
 
 use crate::{
     commands::utils::{SPINNER_SLOW_TICK_RATE, SPINNER_TICK_RATE},
-    credential::{get_default_headers, handle_token},
+    credential::handle_token,
     github::{github_client::GitHub, graphql::get_branches::PullRequestState},
+    network,
     prompts::text::confirm_prompt,
 };
 
@@ -89,9 +90,7 @@ impl RemoveDeadVersions {
     pub async fn run(self) -> Result<()> {
         let token = handle_token(self.token.as_deref()).await?;
         let github = GitHub::new(&token)?;
-        let client = Client::builder()
-            .default_headers(get_default_headers(None))
-            .build()?;
+        let client = network::client(None)?;
 
         let current_user = github.get_username();
         let winget_pkgs = github.get_winget_pkgs().send();
@@ -12,7 +12,7 @@ use winget_types::shared::{PackageIdentifier, PackageVersion};
 
 use crate::{
     credential::handle_token,
-    github::github_client::{GitHub, WINGET_PKGS_FULL_NAME},
+    github::{github_client::GitHub, upstream::upstream},
     prompts::{handle_inquire_error, text::confirm_prompt},
 };
 
@@ -69,9 +69,10 @@ impl RemoveVersion {
 
         if !versions.contains(&self.package_version) {
             bail!(
-                "{} 版本 {} 不存在于 {WINGET_PKGS_FULL_NAME}",
+                "{} 版本 {} 不存在于 {}",
                 self.package_identifier,
                 self.package_version,
+                upstream(),
             );
         }
 
@@ -2,7 +2,10 @@ use clap::Parser;
 use color_eyre::Result;
 use winget_types::shared::{PackageIdentifier, PackageVersion};
 
-use crate::{credential::handle_token, github::github_client::GitHub, manifests::print_changes};
+use crate::{
+    manifests::print_changes,
+    repository::{ManifestRepository, RepositoryArgs},
+};
 
 /// 输出给定包和版本的清单
 #[expect(clippy::struct_excessive_bools)]
@@ -32,6 +35,9 @@ pub struct ShowVersion {
     #[arg(long)]
     version_manifest: bool,
 
+    #[command(flatten)]
+    repository: RepositoryArgs,
+
     /// 具有 `public_repo` 范围的 GitHub 个人访问令牌
     #[arg(short, long, env = "GITHUB_TOKEN")]
     token: Option<String>,
@@ -39,14 +45,13 @@ pub struct ShowVersion {
 
 impl ShowVersion {
     pub async fn run(self) -> Result<()> {
-        let token = handle_token(self.token.as_deref()).await?;
-        let github = GitHub::new(&token)?;
+        let repository = self.repository.open(self.token.as_deref()).await?;
 
         // 获取给定包的所有版本列表
-        let mut versions = github.get_versions(&self.package_identifier).await?;
+        let mut versions = repository.get_versions(&self.package_identifier).await?;
 
         // 获取最新或指定版本的清单
-        let manifests = github
+        let manifests = repository
             .get_manifests(
                 &self.package_identifier,
                 &self
@@ -13,13 +13,10 @@ use winget_types::shared::{GenericManifest, ManifestType};
 
 use crate::{
     commands::utils::{SPINNER_TICK_RATE, SubmitOption, prompt_submit_option},
-    credential::handle_token,
-    github::{
-        github_client::{GitHub, WINGET_PKGS_FULL_NAME},
-        utils::{get_package_path, pull_request::pr_changes},
-    },
+    github::utils::{get_package_path, pull_request::pr_changes},
     manifests::{Manifests, manifest::Manifest},
     prompts::handle_inquire_error,
+    repository::{ManifestRepository, RepositoryArgs, Submission},
 };
 
 #[derive(Parser)]
@@ -39,6 +36,9 @@ pub struct Submit {
     #[arg(long, env = "DRY_RUN")]
     dry_run: bool,
 
+    #[command(flatten)]
+    repository: RepositoryArgs,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN")]
     token: Option<String>,
@@ -46,7 +46,7 @@ pub struct Submit {
 
 impl Submit {
     pub async fn run(self) -> Result<()> {
-        let token = handle_token(self.token.as_deref());
+        let repository = self.repository.open(self.token.as_deref());
 
         let yaml_entries = self.get_yaml_file_paths()?;
 
@@ -148,8 +148,11 @@ impl Submit {
             return Ok(());
         }
 
-        let github = GitHub::new(&token.await?)?;
-        let versions = github.get_versions(identifier).await.unwrap_or_default();
+        let repository = repository.await?;
+        let versions = repository
+            .get_versions(identifier)
+            .await
+            .unwrap_or_default();
 
         // Create an indeterminate progress bar to show as a pull request is being created
         let pr_progress = ProgressBar::new_spinner().with_message(format!(
@@ -157,28 +160,22 @@ impl Submit {
         ));
         pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
 
-        let pull_request_url = github
-            .add_version()
-            .identifier(identifier)
-            .version(version)
-            .versions(&versions)
-            .changes(changes)
-            .send()
+        let submitted = repository
+            .submit(Submission {
+                identifier,
+                version,
+                versions: Some(&versions),
+                changes,
+                replace_version: None,
+                issue_resolves: None,
+                created_with: None,
+                created_with_url: None,
+            })
             .await?;
 
         pr_progress.finish_and_clear();
 
-        println!(
-            "{} 创建了一个拉取请求到 {WINGET_PKGS_FULL_NAME}",
-            "成功".green()
-        );
-        println!("{}", pull_request_url.as_str());
-
-        if self.open_pr {
-            open::that(pull_request_url.as_str())?;
-        }
-
-        Ok(())
+        submitted.report(self.open_pr)
     }
 
     fn get_yaml_file_paths(&self) -> walkdir::Result<Vec<Utf8PathBuf>> {
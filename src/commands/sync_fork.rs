@@ -5,7 +5,9 @@ use indicatif::ProgressBar;
 use owo_colors::OwoColorize;
 
 use crate::{
-    commands::utils::SPINNER_TICK_RATE, credential::handle_token, github::github_client::GitHub,
+    commands::utils::SPINNER_TICK_RATE,
+    credential::handle_token,
+    github::{github_client::GitHub, upstream::upstream},
     hyperlink::Hyperlink,
 };
 
@@ -33,6 +35,8 @@ impl SyncFork {
         let winget_pkgs_fork = github
             .get_winget_pkgs()
             .owner(&github.get_username().await?)
+            // The fork's branch of the same name is kept in sync with a configured upstream branch
+            .maybe_branch(upstream().branch.as_deref())
             .send()
             .await?;
         let winget_pkgs = winget_pkgs.await?;
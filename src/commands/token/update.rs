@@ -2,9 +2,11 @@ use anstream::println;
 use clap::Parser;
 use color_eyre::eyre::Result;
 use owo_colors::OwoColorize;
-use reqwest::Client;
 
-use crate::credential::{get_default_headers, get_komac_credential, token_prompt, validate_token};
+use crate::{
+    credential::{get_komac_credential, token_prompt, validate_token},
+    network,
+};
 
 /// Update the stored token
 #[derive(Parser)]
@@ -19,9 +21,7 @@ impl UpdateToken {
     pub async fn run(self) -> Result<()> {
         let credential = get_komac_credential()?;
 
-        let client = Client::builder()
-            .default_headers(get_default_headers(None))
-            .build()?;
+        let client = network::client(None)?;
 
         let token = match self.token {
             Some(token) => validate_token(&client, &token).await.map(|()| token)?,
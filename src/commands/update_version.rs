@@ -1,20 +1,22 @@
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeSet, HashMap},
     io::{Read, Seek},
     mem,
     num::{NonZeroU8, NonZeroU32},
+    slice,
 };
 
 use anstream::println;
 use camino::Utf8PathBuf;
 use clap::Parser;
 use color_eyre::eyre::{Result, bail};
-use indicatif::ProgressBar;
+use indicatif::{DecimalBytes, ProgressBar};
 use owo_colors::OwoColorize;
 use reqwest::Client;
 use strsim::levenshtein;
+use tracing::warn;
 use winget_types::{
-    installer::{InstallerType, MinimumOSVersion, NestedInstallerFiles},
+    installer::{Installer, InstallerType, MinimumOSVersion, NestedInstallerFiles},
     shared::{
         PackageIdentifier, PackageVersion,
         url::{DecodedUrl, ReleaseNotesUrl},
@@ -23,18 +25,26 @@ use winget_types::{
 };
 
 use crate::{
+    checksums::verify_checksums,
     commands::utils::{
         SPINNER_TICK_RATE, SubmitOption, prompt_existing_pull_request, prompt_submit_option,
         write_changes_to_dir,
     },
-    credential::{get_default_headers, handle_token},
-    download_file::{download_urls, process_files},
+    download_file::{
+        DownloadArgs, DownloadErrors, DownloadOptions, LocalFiles, TooLargeError, download_urls,
+        process_files,
+    },
+    file_analyser::FileAnalyser,
+    forge::get_release_values,
     github::{
-        github_client::{GITHUB_HOST, GitHub, WINGET_PKGS_FULL_NAME},
+        upstream::upstream,
         utils::{get_package_path, pull_request::pr_changes},
     },
+    hash_drift::find_hash_drift,
     installers::zip::Zip,
     match_installers::match_installers,
+    network,
+    repository::{ManifestRepository, RepositoryArgs, Submission, Submitted},
     traits::{LocaleExt, path::NormalizePath},
 };
 
@@ -57,6 +67,12 @@ pub struct UpdateVersion {
     #[arg(long, default_value_t = NonZeroU8::new(2).unwrap())]
     concurrent_downloads: NonZeroU8,
 
+    #[command(flatten)]
+    download: DownloadArgs,
+
+    #[command(flatten)]
+    local_files: LocalFiles,
+
     /// List of issues that updating this package would resolve
     #[arg(long)]
     resolves: Option<Vec<NonZeroU32>>,
@@ -97,6 +113,24 @@ pub struct UpdateVersion {
     #[arg(long, env)]
     skip_pr_check: bool,
 
+    /// Push the manifests onto an open pull request from your fork for the same version instead
+    /// of creating another one
+    #[arg(long, conflicts_with_all = ["skip_pr_check", "local_repository"])]
+    amend_existing: bool,
+
+    /// Submit even if an installer URL that was used by the previous version now has a different
+    /// hash
+    #[arg(long)]
+    allow_unversioned_url: bool,
+
+    /// Don't download the previous version's installers to check whether installers that aren't
+    /// signed by the publisher were signed by someone else before
+    #[arg(long)]
+    no_compare_signers: bool,
+
+    #[command(flatten)]
+    repository: RepositoryArgs,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN")]
     token: Option<String>,
@@ -104,16 +138,14 @@ pub struct UpdateVersion {
 
 impl UpdateVersion {
     pub async fn run(self) -> Result<()> {
-        let token = handle_token(self.token.as_deref()).await?;
-        let github = GitHub::new(&token)?;
-        let client = Client::builder()
-            .default_headers(get_default_headers(None))
-            .build()?;
+        let repository = self.repository.open(self.token.as_deref()).await?;
+        let client = network::client(None)?;
 
-        let existing_pr =
-            github.get_existing_pull_request(&self.package_identifier, &self.package_version);
+        let existing_pr = repository.github().map(|github| {
+            github.get_existing_pull_request(&self.package_identifier, &self.package_version)
+        });
 
-        let versions = github.get_versions(&self.package_identifier).await?;
+        let versions = repository.get_versions(&self.package_identifier).await?;
 
         let latest_version = versions.last().unwrap_or_else(|| unreachable!());
         println!(
@@ -137,12 +169,30 @@ impl UpdateVersion {
             if !versions.contains(version) {
                 let closest = version.closest(&versions).unwrap_or_else(|| unreachable!());
                 bail!(
-                    "Replacement version {version} does not exist in {WINGET_PKGS_FULL_NAME}. The closest version is {closest}"
+                    "Replacement version {version} does not exist in {}. The closest version is {closest}",
+                    upstream()
                 )
             }
         }
 
-        if let Some(pull_request) = existing_pr.await? {
+        let existing_pr = match existing_pr {
+            Some(future) => future.await?,
+            None => None,
+        };
+        let amend = match (repository.github(), &existing_pr) {
+            (Some(github), Some(pull_request)) if self.amend_existing => {
+                github.can_amend(pull_request).await?
+            }
+            _ => false,
+        };
+        let (amended_pr, existing_pr) = match existing_pr {
+            Some(pull_request) if amend => {
+                println!("将更新现有的拉取请求 {}", pull_request.url.blue());
+                (Some(pull_request), None)
+            }
+            existing_pr => (None, existing_pr),
+        };
+        if let Some(pull_request) = existing_pr {
             if !(self.skip_pr_check || self.dry_run)
                 && !prompt_existing_pull_request(
                     &self.package_identifier,
@@ -154,14 +204,33 @@ impl UpdateVersion {
             }
         }
 
-        let manifests = github.get_manifests(&self.package_identifier, latest_version);
-        let github_values = self
-            .urls
-            .iter()
-            .find(|url| url.host_str() == Some(GITHUB_HOST))
-            .and_then(|url| github.get_all_values_from_url(url));
-
-        let mut files = download_urls(&client, self.urls, self.concurrent_downloads).await?;
+        let manifests = repository.get_manifests(&self.package_identifier, latest_version);
+        let github_values = get_release_values(&self.urls, repository.github(), &client);
+
+        let cache = self.download.cache();
+        let download_options = self.download.options(cache.as_ref());
+        let mut files = self
+            .local_files
+            .fetch(
+                &client,
+                self.urls,
+                self.concurrent_downloads,
+                download_options,
+            )
+            .await?;
+        let mut github_values = match github_values {
+            Some(future) => future.await?,
+            None => None,
+        };
+        verify_checksums(
+            &client,
+            &files,
+            github_values
+                .as_ref()
+                .map(|github_values| github_values.release_assets.as_slice())
+                .unwrap_or_default(),
+        )
+        .await?;
         let mut download_results = process_files(&mut files).await?;
         let installer_results = download_results
             .iter_mut()
@@ -183,7 +252,24 @@ impl UpdateVersion {
                 installer
             })
             .collect::<Vec<_>>();
+        let hash_drift = find_hash_drift(&previous_installers, &installer_results);
+        for hash_drift in &hash_drift {
+            warn!("{hash_drift}");
+        }
+        if !hash_drift.is_empty() && !(self.allow_unversioned_url || self.dry_run) {
+            bail!(
+                "安装程序 URL 必须指向特定版本，winget-pkgs 不接受内容会随时间变化的 URL。使用 --allow-unversioned-url 以仍然提交"
+            );
+        }
         let matched_installers = match_installers(previous_installers, &installer_results);
+        check_signers(
+            &client,
+            &matched_installers,
+            &download_results,
+            &manifests.default_locale.publisher.to_string(),
+            (!self.no_compare_signers).then_some(download_options),
+        )
+        .await;
         let installers = matched_installers
             .into_iter()
             .map(|(previous_installer, new_installer)| {
@@ -192,12 +278,20 @@ impl UpdateVersion {
                     Some(InstallerType::Portable) => previous_installer.r#type,
                     _ => match new_installer.r#type {
                         Some(InstallerType::Portable) => previous_installer.r#type,
-                        _ => new_installer.r#type,
+                        // Files too large to analyse may not have a type of their own
+                        _ => new_installer.r#type.or(previous_installer.r#type),
                     },
                 };
+                // A package that was too large to analyse has no signature of its own, and the
+                // previous one no longer applies once the package has changed
+                let stale_signature = new_installer.signature_sha_256.is_none()
+                    && new_installer.sha_256 != previous_installer.sha_256;
                 let mut installer = new_installer.clone().merge_with(previous_installer);
                 installer.r#type = installer_type;
                 installer.url.clone_from(&new_installer.url);
+                if stale_signature {
+                    installer.signature_sha_256 = None;
+                }
                 installer.nested_installer_files = installer
                     .nested_installer_files
                     .or_else(|| manifests.installer.nested_installer_files.clone())
@@ -222,11 +316,6 @@ impl UpdateVersion {
             .installer
             .reorder_keys(&self.package_identifier, &self.package_version);
 
-        let mut github_values = match github_values {
-            Some(future) => Some(future.await?),
-            None => None,
-        };
-
         manifests.default_locale.update(
             &self.package_version,
             &mut github_values,
@@ -272,39 +361,49 @@ impl UpdateVersion {
             return Ok(());
         }
 
-        // 创建一个不确定的进度条，以显示正在创建拉取请求
+        // 创建一个不确定的进度条，以显示正在创建或更新拉取请求
+        let action = if amended_pr.is_some() {
+            "更新"
+        } else {
+            "创建"
+        };
         let pr_progress = ProgressBar::new_spinner().with_message(format!(
-            "正在为 {} 版本 {} 创建拉取请求",
+            "正在为 {} 版本 {} {action}拉取请求",
             self.package_identifier, self.package_version
         ));
         pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
 
-        let pull_request_url = github
-            .add_version()
-            .identifier(&self.package_identifier)
-            .version(&self.package_version)
-            .versions(&versions)
-            .changes(changes)
-            .maybe_replace_version(replace_version)
-            .maybe_issue_resolves(self.resolves)
-            .maybe_created_with(self.created_with)
-            .maybe_created_with_url(self.created_with_url)
-            .send()
-            .await?;
+        let submitted = match (repository.github(), amended_pr) {
+            (Some(github), Some(pull_request)) => Submitted::AmendedPullRequest(
+                github
+                    .amend_pull_request()
+                    .identifier(&self.package_identifier)
+                    .version(&self.package_version)
+                    .versions(&versions)
+                    .pull_request(pull_request)
+                    .changes(changes)
+                    .send()
+                    .await?,
+            ),
+            _ => {
+                repository
+                    .submit(Submission {
+                        identifier: &self.package_identifier,
+                        version: &self.package_version,
+                        versions: Some(&versions),
+                        changes,
+                        replace_version,
+                        issue_resolves: self.resolves,
+                        created_with: self.created_with,
+                        created_with_url: self.created_with_url,
+                    })
+                    .await?
+            }
+        };
 
         pr_progress.finish_and_clear();
 
-        println!(
-            "{} 创建了一个拉取请求到 {WINGET_PKGS_FULL_NAME}",
-            "成功".green()
-        );
-        println!("{}", pull_request_url.as_str());
-
-        if self.open_pr {
-            open::that(pull_request_url.as_str())?;
-        }
-
-        Ok(())
+        submitted.report(self.open_pr)
     }
 }
 
@@ -346,3 +445,144 @@ fn validate_relative_paths<R: Read + Seek>(
         Some(relative_paths)
     }
 }
+
+/// Warns when a new installer is signed by someone other than the publisher, or is unsigned while
+/// the installer it replaces was signed.
+///
+/// The previous installers are only downloaded for the new installers that are unsigned or not
+/// signed by the publisher, and only up to the analysis threshold, as the previous signer is not
+/// recorded anywhere else.
+async fn check_signers(
+    client: &Client,
+    matched_installers: &HashMap<Installer, Installer>,
+    download_results: &HashMap<DecodedUrl, FileAnalyser<'_>>,
+    publisher: &str,
+    compare_previous: Option<DownloadOptions<'_>>,
+) {
+    let suspicious = matched_installers
+        .iter()
+        .filter(|(_, new_installer)| {
+            matches!(
+                new_installer.r#type,
+                Some(
+                    InstallerType::Exe
+                        | InstallerType::Burn
+                        | InstallerType::Inno
+                        | InstallerType::Nullsoft
+                        | InstallerType::Portable
+                        | InstallerType::Msix
+                        | InstallerType::Appx
+                )
+            )
+        })
+        .filter(|(_, new_installer)| {
+            download_results[&new_installer.url]
+                .signer
+                .as_ref()
+                .is_none_or(|signer| !signer.is_publisher(publisher))
+        })
+        .map(|(previous_installer, new_installer)| {
+            (previous_installer.url.clone(), new_installer.url.clone())
+        })
+        .collect::<Vec<_>>();
+
+    if suspicious.is_empty() {
+        return;
+    }
+
+    let Some(download_options) = compare_previous else {
+        for (_, new_url) in &suspicious {
+            if let Some(new_signer) = download_results[new_url].signer.as_ref() {
+                warn!(
+                    "{new_url} 的签名者 \"{}\" 与发布者 \"{publisher}\" 不一致",
+                    new_signer.subject
+                );
+            }
+        }
+        return;
+    };
+
+    // Previous installers that are too large to analyse have no signer to compare against, so
+    // they are not downloaded at all
+    let download_options = DownloadOptions {
+        max_size: download_options.max_analysis_size,
+        allow_vanity_url: true,
+        ..download_options
+    };
+    let mut previous_signers = HashMap::new();
+    for (previous_url, _) in &suspicious {
+        let previous_files = match download_urls(
+            client,
+            vec![previous_url.clone()],
+            NonZeroU8::MIN,
+            download_options,
+        )
+        .await
+        {
+            Ok(previous_files) => previous_files,
+            Err(error) if is_too_large(&error) => {
+                warn!(
+                    "{previous_url} 超过了最大分析大小 ({})，跳过与之前版本签名者的比较",
+                    DecimalBytes(download_options.max_size)
+                );
+                continue;
+            }
+            Err(error) => {
+                warn!("无法下载之前版本的安装程序以比较签名者: {error}");
+                continue;
+            }
+        };
+        // The URL a file is recorded under can differ from the one it was requested with, such as
+        // after an upgrade to HTTPS, so the single file is taken by position instead
+        let Some(mut previous_file) = previous_files.into_iter().next() else {
+            continue;
+        };
+        match process_files(slice::from_mut(&mut previous_file)).await {
+            Ok(results) => {
+                if let Some(signer) = results
+                    .into_values()
+                    .next()
+                    .and_then(|analyser| analyser.signer)
+                {
+                    previous_signers.insert(previous_url.clone(), signer);
+                }
+            }
+            Err(error) => warn!("无法分析之前版本的安装程序以比较签名者: {error}"),
+        }
+    }
+
+    for (previous_url, new_url) in suspicious {
+        let previous_signer = previous_signers.get(&previous_url);
+        let new_signer = download_results[&new_url].signer.as_ref();
+        match (previous_signer, new_signer) {
+            (Some(previous_signer), None) => warn!(
+                "{new_url} 未签名，但之前的版本由 \"{}\" 签名",
+                previous_signer.subject
+            ),
+            (Some(previous_signer), Some(new_signer))
+                if !new_signer.is_subject(&previous_signer.subject) =>
+            {
+                warn!(
+                    "{new_url} 的签名者 \"{}\" 与之前版本的签名者 \"{}\" 不一致",
+                    new_signer.subject, previous_signer.subject
+                );
+            }
+            (None, Some(new_signer)) => warn!(
+                "{new_url} 的签名者 \"{}\" 与发布者 \"{publisher}\" 不一致",
+                new_signer.subject
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Returns whether a download failed only because the file was larger than allowed.
+fn is_too_large(error: &color_eyre::Report) -> bool {
+    error
+        .downcast_ref::<DownloadErrors>()
+        .is_some_and(|errors| {
+            errors
+                .iter()
+                .all(|error| error.downcast_ref::<TooLargeError>().is_some())
+        })
+}
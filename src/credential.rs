@@ -9,20 +9,25 @@ use reqwest::{
 };
 use tokio::runtime::Handle;
 
-use crate::prompts::handle_inquire_error;
+use crate::{github::api::github_api, network, prompts::handle_inquire_error};
 
 const SERVICE: &str = "komac";
 const USERNAME: &str = "github-access-token";
-const GITHUB_API_ENDPOINT: &str = "https://api.github.com/octocat";
+const GITHUB_API_ENDPOINT: &str = "octocat";
 
+/// Returns the stored token for the configured GitHub instance. Tokens for instances other than
+/// github.com are stored separately, as a token only works on the instance that issued it.
 pub fn get_komac_credential() -> keyring::Result<Entry> {
-    Entry::new(SERVICE, USERNAME)
+    let github_api = github_api();
+    if github_api.is_default() {
+        Entry::new(SERVICE, USERNAME)
+    } else {
+        Entry::new(SERVICE, &format!("{USERNAME}@{}", github_api.host()))
+    }
 }
 
 pub async fn handle_token(token: Option<&str>) -> Result<Cow<str>> {
-    let client = Client::builder()
-        .default_headers(get_default_headers(None))
-        .build()?;
+    let client = network::client(None)?;
 
     if let Some(token) = token {
         return validate_token(&client, token)
@@ -63,7 +68,7 @@ pub fn token_prompt(client: Client, prompt: Option<&str>) -> InquireResult<Strin
 
 pub async fn validate_token(client: &Client, token: &str) -> Result<()> {
     match client
-        .get(GITHUB_API_ENDPOINT)
+        .get(github_api().rest_url(GITHUB_API_ENDPOINT)?)
         .bearer_auth(token)
         .send()
         .await
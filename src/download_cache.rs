@@ -0,0 +1,147 @@
+use std::{fs, fs::File, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tracing::{debug, warn};
+
+const PAYLOAD_EXTENSION: &str = "bin";
+const METADATA_EXTENSION: &str = "json";
+
+/// A persistent cache of downloaded installers, so that running Komac again for the same URLs
+/// (such as after a failed submission) does not download every installer again.
+///
+/// Each entry is keyed by the SHA-256 of its URL and stores the payload next to a JSON file
+/// containing the validators needed to revalidate it with a conditional request.
+pub struct DownloadCache {
+    directory: Utf8PathBuf,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub file_name: String,
+    pub etag: Option<String>,
+    /// The raw `Last-Modified` header, sent back as `If-Modified-Since`
+    pub last_modified: Option<String>,
+    pub sha_256: String,
+    pub size: u64,
+    pub last_used: DateTime<Utc>,
+}
+
+impl CacheEntry {
+    fn key(&self) -> String {
+        DownloadCache::key(&self.url)
+    }
+}
+
+impl DownloadCache {
+    /// Opens the cache in the user's cache directory, or returns `None` if it cannot be used.
+    pub fn open() -> Option<Self> {
+        let directory = dirs::cache_dir()
+            .and_then(|cache_dir| Utf8PathBuf::from_path_buf(cache_dir).ok())?
+            .join(env!("CARGO_PKG_NAME"))
+            .join("downloads");
+
+        match fs::create_dir_all(&directory) {
+            Ok(()) => Some(Self { directory }),
+            Err(error) => {
+                warn!("无法创建下载缓存目录 {directory}: {error}");
+                None
+            }
+        }
+    }
+
+    pub fn directory(&self) -> &Utf8Path {
+        &self.directory
+    }
+
+    fn key(url: &str) -> String {
+        format!("{:x}", Sha256::digest(url.as_bytes()))
+    }
+
+    fn payload_path(&self, key: &str) -> Utf8PathBuf {
+        self.directory.join(key).with_extension(PAYLOAD_EXTENSION)
+    }
+
+    fn metadata_path(&self, key: &str) -> Utf8PathBuf {
+        self.directory.join(key).with_extension(METADATA_EXTENSION)
+    }
+
+    /// Returns the cached entry for a URL if both its metadata and payload are present.
+    pub fn get(&self, url: &str) -> Option<CacheEntry> {
+        let key = Self::key(url);
+        let entry = fs::read(self.metadata_path(&key))
+            .ok()
+            .and_then(|metadata| serde_json::from_slice::<CacheEntry>(&metadata).ok())?;
+
+        let payload_size = fs::metadata(self.payload_path(&key)).ok()?.len();
+        if entry.url != url || payload_size != entry.size {
+            debug!(url, "Discarding inconsistent cache entry");
+            self.remove(&entry).ok();
+            return None;
+        }
+
+        Some(entry)
+    }
+
+    /// Opens the payload of a cached entry and records that it was used.
+    pub fn open_payload(&self, entry: &mut CacheEntry) -> io::Result<File> {
+        let file = File::open(self.payload_path(&entry.key()))?;
+        entry.last_used = Utc::now();
+        if let Err(error) = self.write_metadata(entry) {
+            debug!(%error, "Failed to update cache entry");
+        }
+        Ok(file)
+    }
+
+    /// Creates a temporary file in the cache directory to download a payload into, so that it can
+    /// be moved into place without copying once the download has finished.
+    pub fn new_payload(&self) -> io::Result<NamedTempFile> {
+        NamedTempFile::new_in(&self.directory)
+    }
+
+    /// Moves a downloaded payload into the cache and writes its metadata.
+    pub fn insert(&self, payload: NamedTempFile, entry: &CacheEntry) -> Result<()> {
+        payload.persist(self.payload_path(&entry.key()))?;
+        self.write_metadata(entry)?;
+        Ok(())
+    }
+
+    fn write_metadata(&self, entry: &CacheEntry) -> io::Result<()> {
+        fs::write(
+            self.metadata_path(&entry.key()),
+            serde_json::to_vec_pretty(entry)?,
+        )
+    }
+
+    pub fn remove(&self, entry: &CacheEntry) -> io::Result<()> {
+        let key = entry.key();
+        for path in [self.payload_path(&key), self.metadata_path(&key)] {
+            match fs::remove_file(path) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns every entry in the cache, most recently used first.
+    pub fn entries(&self) -> io::Result<Vec<CacheEntry>> {
+        let mut entries = fs::read_dir(&self.directory)?
+            .filter_map(Result::ok)
+            .map(|dir_entry| dir_entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == METADATA_EXTENSION)
+            })
+            .filter_map(|path| fs::read(path).ok())
+            .filter_map(|metadata| serde_json::from_slice::<CacheEntry>(&metadata).ok())
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| b.last_used.cmp(&a.last_used));
+        Ok(entries)
+    }
+}
@@ -1,41 +1,97 @@
-use std::{cmp::min, collections::HashMap, fs::File, mem, num::NonZeroU8};
+use std::{
+    cmp::min, collections::HashMap, fmt, fs::File, io, mem, num::NonZeroU8, str::FromStr,
+    time::Duration,
+};
 
-use camino::Utf8Path;
-use chrono::{DateTime, NaiveDate};
-use color_eyre::eyre::{Result, bail, eyre};
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Args;
+use color_eyre::eyre::{Result, WrapErr, bail, eyre};
 use const_format::formatcp;
 use futures_util::{StreamExt, TryStreamExt, stream};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use itertools::Itertools;
-use memmap2::Mmap;
+use indicatif::{DecimalBytes, MultiProgress, ProgressBar, ProgressStyle};
+use itertools::{Either, Itertools};
 use reqwest::{
-    Client,
-    header::{CONTENT_DISPOSITION, HeaderValue, LAST_MODIFIED},
+    Client, RequestBuilder, Response, StatusCode,
+    header::{
+        CONTENT_DISPOSITION, ETAG, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE,
+        LAST_MODIFIED, RANGE,
+    },
 };
 use sha2::{Digest, Sha256};
-use tokio::io::AsyncWriteExt;
+use thiserror::Error;
+use tokio::{sync::Semaphore, time::sleep};
+use tracing::{info, warn};
 use url::Url;
 use uuid::Uuid;
 use winget_types::{
-    installer::{Architecture, VALID_FILE_EXTENSIONS},
+    installer::{Architecture, InstallerType, VALID_FILE_EXTENSIONS},
     shared::{Sha256String, url::DecodedUrl},
 };
 
 use crate::{
-    file_analyser::FileAnalyser,
-    traits::url::{ConvertGitHubLatestToVersioned, UpgradeToHttps},
+    download_cache::{CacheEntry, DownloadCache},
+    file_analyser::{APP_INSTALLER, FileAnalyser},
+    installers::{
+        app_installer::AppInstaller,
+        utils::release_date::{ReleaseDate, ReleaseDateSource},
+    },
+    network,
+    payload::{Payload, Sink},
+    traits::url::{Resolution, ResolveVanityUrl, UpgradeToHttps},
 };
 
 async fn download_file(
     client: &Client,
     mut url: DecodedUrl,
     multi_progress: &MultiProgress,
+    options: DownloadOptions<'_>,
 ) -> Result<DownloadedFile> {
-    url.convert_github_latest_to_versioned().await?;
+    match url.resolve_vanity_url().await? {
+        Resolution::Versioned => {}
+        // Hosts such as GitHub always redirect their latest release links, so a URL that wasn't
+        // redirected is not safe to publish, whereas a channel URL may just be served directly
+        Resolution::Unresolved {
+            always_redirects: true,
+        } if !options.allow_vanity_url => {
+            bail!(
+                "{url} 似乎始终指向最新版本，但无法解析出带版本号的 URL。使用 --allow-vanity-url 以仍然使用此 URL"
+            );
+        }
+        Resolution::Unresolved { .. } => {
+            warn!("{url} 似乎始终指向最新版本，但无法解析出带版本号的 URL");
+        }
+    }
 
     url.upgrade_to_https(client).await;
 
-    let res = client.get(url.as_str()).send().await?;
+    let mut cached_entry = options.cache.and_then(|cache| cache.get(url.as_str()));
+
+    let mut request = client.get(url.as_str());
+    if let Some(entry) = &cached_entry {
+        if let Some(etag) = &entry.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let mut res = send_with_retries(request, options.retries).await?;
+
+    // A 304 only means the remote file is unchanged, not that the cached copy is still intact
+    let mut cached_file = None;
+    if let (StatusCode::NOT_MODIFIED, Some(cache), Some(entry)) =
+        (res.status(), options.cache, cached_entry.as_mut())
+    {
+        match open_verified_payload(cache, entry)? {
+            Some(file) => cached_file = Some(file),
+            None => {
+                warn!("{url} 的缓存文件与记录的 SHA-256 不符，将重新下载");
+                cache.remove(entry)?;
+                res = send_with_retries(client.get(url.as_str()), options.retries).await?;
+            }
+        }
+    }
 
     if let Err(err) = res.error_for_status_ref() {
         bail!(
@@ -45,54 +101,311 @@ async fn download_file(
         )
     }
 
-    let content_disposition = res.headers().get(CONTENT_DISPOSITION);
-    let file_name = get_file_name(&url, res.url(), content_disposition);
-    let total_size = res
-        .content_length()
-        .ok_or_else(|| eyre!("无法从 '{url}' 获取内容长度"))?;
-
-    let last_modified = res
-        .headers()
-        .get(LAST_MODIFIED)
-        .and_then(|last_modified| last_modified.to_str().ok())
+    let (payload, file_name, sha_256, last_modified) =
+        if let (Some(file), Some(entry)) = (cached_file, &cached_entry) {
+            info!("{url} 未更改，使用缓存的文件");
+            let payload = if entry.size > options.max_analysis_size {
+                Payload::Skipped
+            } else {
+                Payload::map(file)?
+            };
+            (
+                payload,
+                entry.file_name.clone(),
+                entry.sha_256.parse::<Sha256String>()?,
+                entry.last_modified.clone(),
+            )
+        } else {
+            download_response(client, &url, res, multi_progress, options).await?
+        };
+
+    let last_modified = last_modified
+        .as_deref()
         .and_then(|last_modified| DateTime::parse_from_rfc2822(last_modified).ok())
         .map(|date_time| date_time.date_naive());
 
-    let pb = multi_progress.add(ProgressBar::new(total_size)
-        .with_style(ProgressStyle::default_bar()
-            .template("{msg}\n{wide_bar:.magenta/black} {decimal_bytes:.green}/{decimal_total_bytes:.green} {decimal_bytes_per_sec:.red} eta {eta:.blue}")?
-            .progress_chars("───")
-        )
-        .with_message(format!("正在下载 {url}"))
-    );
+    // App Installer files point at the versioned package, which is what should be analysed and
+    // published in the manifest
+    if let Some(data) = payload.data().filter(|_| {
+        Utf8Path::new(&file_name)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(APP_INSTALLER))
+    }) {
+        let app_installer = AppInstaller::new(data)?;
+        let main_package_url = app_installer.main_package.uri.clone();
+        info!("{url} 指向 {main_package_url}");
+        if let Some(update_settings) = &app_installer.update_settings {
+            info!("{file_name} 的更新设置: {update_settings}");
+        }
+        let mut downloaded_file = Box::pin(download_file(
+            client,
+            main_package_url,
+            multi_progress,
+            options,
+        ))
+        .await?;
+        downloaded_file.app_installer = Some(app_installer);
+        return Ok(downloaded_file);
+    }
+
+    Ok(DownloadedFile {
+        url,
+        payload,
+        sha_256,
+        file_name,
+        last_modified,
+        app_installer: None,
+    })
+}
+
+/// Opens the payload of a cached entry, or returns `None` if its contents no longer match the
+/// recorded hash.
+fn open_verified_payload(cache: &DownloadCache, entry: &mut CacheEntry) -> Result<Option<File>> {
+    let mut file = cache.open_payload(entry)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    if Sha256String::from_hasher(&hasher.finalize())? != entry.sha_256.parse::<Sha256String>()? {
+        return Ok(None);
+    }
+    Ok(Some(file))
+}
+
+#[derive(Copy, Clone)]
+pub struct DownloadOptions<'cache> {
+    pub cache: Option<&'cache DownloadCache>,
+    /// The number of times to retry a failed request or an interrupted download
+    pub retries: u8,
+    /// The largest file in bytes that will be downloaded, which guards against endpoints that
+    /// stream indefinitely when no `Content-Length` is sent
+    pub max_size: u64,
+    /// The largest file in bytes that will be analysed. Larger files are hashed as they stream in
+    /// without being written anywhere.
+    pub max_analysis_size: u64,
+    /// Whether to download a URL that always points at the latest release when the versioned URL
+    /// it redirects to can't be found, rather than failing. This only applies to hosts that
+    /// always redirect such URLs, as other URLs are only warned about.
+    pub allow_vanity_url: bool,
+}
+
+/// Status codes that indicate a temporary problem with the server, which are worth retrying.
+const TRANSIENT_STATUSES: [StatusCode; 6] = [
+    StatusCode::REQUEST_TIMEOUT,
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
+/// Returns how long to wait before the given retry, doubling each time from half a second.
+fn backoff(attempt: u8) -> Duration {
+    const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+    const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+    min(
+        INITIAL_BACKOFF.saturating_mul(1 << min(attempt.saturating_sub(1), 16)),
+        MAX_BACKOFF,
+    )
+}
+
+/// Sends a request, retrying connection errors and transient server errors with exponential
+/// backoff.
+async fn send_with_retries(request: RequestBuilder, retries: u8) -> Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let result = request
+            .try_clone()
+            .ok_or_else(|| eyre!("无法重试请求"))?
+            .send()
+            .await;
+        let error = match result {
+            Ok(res) if !TRANSIENT_STATUSES.contains(&res.status()) || attempt >= retries => {
+                return Ok(res);
+            }
+            Ok(res) => format!("{} 返回 {}", res.url(), res.status()),
+            Err(error) if attempt < retries && (error.is_connect() || error.is_timeout()) => {
+                error.to_string()
+            }
+            Err(error) => return Err(error.into()),
+        };
+        attempt += 1;
+        let delay = backoff(attempt);
+        warn!("{error}，将在 {delay:?} 后重试 ({attempt}/{retries})");
+        sleep(delay).await;
+    }
+}
+
+/// Streams a response body while hashing it, returning its payload, its name, its hash and the raw
+/// `Last-Modified` header.
+///
+/// Installers need random access to be analysed, so they are still written to disk in full. Only
+/// other formats are kept in memory, and files over the analysis threshold are not kept at all.
+///
+/// If the connection drops part way through, the download is resumed from where it stopped with a
+/// `Range` request. Servers that ignore the range send the whole file again, in which case the
+/// download starts over.
+///
+/// Responses with a validator are downloaded straight into the cache so that they can be
+/// revalidated next time instead of downloaded again.
+async fn download_response(
+    client: &Client,
+    url: &DecodedUrl,
+    mut res: Response,
+    multi_progress: &MultiProgress,
+    options: DownloadOptions<'_>,
+) -> Result<(Payload, String, Sha256String, Option<String>)> {
+    let content_disposition = res.headers().get(CONTENT_DISPOSITION);
+    let file_name = get_file_name(url, res.url(), content_disposition);
+    // Chunked responses and dynamic download endpoints often have no Content-Length
+    let total_size = res.content_length();
+    if let Some(total_size) = total_size.filter(|&total_size| total_size > options.max_size) {
+        return Err(TooLargeError::ContentLength {
+            url: url.clone(),
+            size: total_size,
+            max_size: options.max_size,
+        }
+        .into());
+    }
+
+    let header = |name| {
+        res.headers()
+            .get(name)
+            .and_then(|value: &HeaderValue| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+
+    let pb = multi_progress.add(match total_size {
+        Some(total_size) => ProgressBar::new(total_size)
+            .with_style(ProgressStyle::default_bar()
+                .template("{msg}\n{wide_bar:.magenta/black} {decimal_bytes:.green}/{decimal_total_bytes:.green} {decimal_bytes_per_sec:.red} eta {eta:.blue}")?
+                .progress_chars("───")
+            ),
+        None => ProgressBar::no_length()
+            .with_style(ProgressStyle::default_spinner()
+                .template("{msg}\n{spinner:.magenta} {decimal_bytes:.green} {decimal_bytes_per_sec:.red} {elapsed:.blue}")?
+            ),
+    }
+    .with_message(format!("正在下载 {url}")));
+
+    let analyse = total_size.is_none_or(|total_size| total_size <= options.max_analysis_size);
+    if !analyse {
+        info!(
+            "{url} 的大小超过了最大分析大小 ({})，只计算其 SHA-256",
+            DecimalBytes(options.max_analysis_size)
+        );
+    }
+
+    let mut cache = options
+        .cache
+        .filter(|_| analyse && (etag.is_some() || last_modified.is_some()));
+    let mut cache_payload = cache.map(DownloadCache::new_payload).transpose()?;
 
     // 下载块
-    let temp_file = tempfile::tempfile()?;
-    let mut file = tokio::fs::File::from_std(temp_file.try_clone()?);
+    let mut sink = match &cache_payload {
+        Some(cache_payload) => Sink::disk(cache_payload.as_file().try_clone()?),
+        None if analyse => Sink::Sniffing(Vec::new()),
+        None => Sink::Discard,
+    };
     let mut downloaded = 0;
-    let mut stream = res.bytes_stream();
+    let final_url = res.url().clone();
+
+    // A weak ETag cannot be used to resume a download, so fall back to the modification date
+    let range_validator = etag
+        .as_deref()
+        .filter(|etag| !etag.starts_with("W/"))
+        .or(last_modified.as_deref())
+        .map(str::to_owned);
 
     let mut hasher = Sha256::new();
-    while let Some(item) = stream.next().await {
-        let chunk = item?;
-        let write = file.write_all(&chunk);
-        hasher.update(&chunk); // 在下载时对文件进行哈希
-        downloaded = min(downloaded + (chunk.len() as u64), total_size);
-        pb.set_position(downloaded);
-        write.await?;
-    }
-    file.flush().await?;
-    file.sync_all().await?;
+    let mut attempt = 0;
+    loop {
+        let mut stream = res.bytes_stream();
+        let stream_error = loop {
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    hasher.update(&chunk); // 在下载时对文件进行哈希
+                    downloaded += chunk.len() as u64;
+                    if downloaded > options.max_size {
+                        return Err(TooLargeError::Streamed {
+                            url: url.clone(),
+                            max_size: options.max_size,
+                        }
+                        .into());
+                    }
+                    // Responses without a Content-Length can only be checked once they get there
+                    if downloaded > options.max_analysis_size && !matches!(sink, Sink::Discard) {
+                        info!(
+                            "{url} 超过了最大分析大小 ({})，只计算其 SHA-256",
+                            DecimalBytes(options.max_analysis_size)
+                        );
+                        sink = Sink::Discard;
+                        cache = None;
+                        cache_payload = None;
+                    }
+                    pb.set_position(
+                        total_size.map_or(downloaded, |total_size| min(downloaded, total_size)),
+                    );
+                    sink.write(&chunk).await?;
+                }
+                Some(Err(error)) => break Some(error),
+                None => break None,
+            }
+        };
+
+        let Some(stream_error) = stream_error else {
+            break;
+        };
+        if attempt >= options.retries {
+            return Err(stream_error.into());
+        }
+        attempt += 1;
+        let delay = backoff(attempt);
+        warn!(
+            "下载 {url} 时出错: {stream_error}，将在 {delay:?} 后从第 {downloaded} 字节继续 ({attempt}/{})",
+            options.retries
+        );
+        sleep(delay).await;
+
+        let mut request = client
+            .get(final_url.as_str())
+            .header(RANGE, format!("bytes={downloaded}-"));
+        if let Some(range_validator) = &range_validator {
+            request = request.header(IF_RANGE, range_validator);
+        }
+        res = send_with_retries(request, options.retries - attempt).await?;
+        res.error_for_status_ref()?;
+
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            info!("{url} 不支持断点续传，重新开始下载");
+            sink.reset().await?;
+            hasher = Sha256::new();
+            downloaded = 0;
+            pb.set_position(0);
+        }
+    }
+    let payload = sink.finish().await?;
     pb.finish_and_clear();
 
-    Ok(DownloadedFile {
-        url,
-        mmap: unsafe { Mmap::map(&temp_file) }?,
-        file: temp_file,
-        sha_256: Sha256String::from_hasher(&hasher.finalize())?,
-        file_name,
-        last_modified,
-    })
+    let sha_256 = Sha256String::from_hasher(&hasher.finalize())?;
+
+    if let (Some(cache), Some(cache_payload)) = (cache, cache_payload) {
+        let entry = CacheEntry {
+            url: url.to_string(),
+            file_name: file_name.clone(),
+            etag,
+            last_modified: last_modified.clone(),
+            sha_256: sha_256.to_string(),
+            size: downloaded,
+            last_used: Utc::now(),
+        };
+        if let Err(error) = cache.insert(cache_payload, &entry) {
+            warn!("无法缓存 {url}: {error}");
+        }
+    }
+
+    Ok((payload, file_name, sha_256, last_modified))
 }
 
 /// 从 URL 获取文件名，给定 URL、最终重定向的 URL 和可选的 Content-Disposition 头。
@@ -154,35 +467,314 @@ fn get_file_name(url: &Url, final_url: &Url, content_disposition: Option<&Header
         .map_or_else(|| Uuid::new_v4().to_string(), str::to_owned)
 }
 
+/// Downloads every URL, continuing with the rest when one of them fails so that every failure can
+/// be reported at once in a single [`DownloadErrors`]. Files that did download are still kept in
+/// the download cache if they could be cached.
+///
+/// The files are returned in the same order as the unique URLs they were downloaded from, as the
+/// URL a file ends up recorded under can differ from the one it was requested with.
 pub async fn download_urls(
     client: &Client,
     urls: Vec<DecodedUrl>,
     concurrent_downloads: NonZeroU8,
+    options: DownloadOptions<'_>,
 ) -> Result<Vec<DownloadedFile>> {
+    let urls = urls.into_iter().unique().collect::<Vec<_>>();
+
+    // Limits how many of the concurrent downloads can be from the same host
+    let host_permits = network::max_connections_per_host()
+        .map(|max_connections| {
+            let permits = max_connections.get() as usize;
+            urls.iter()
+                .filter_map(|url| url.host_str())
+                .map(|host| (host.to_owned(), Semaphore::new(permits)))
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
     let multi_progress = MultiProgress::new();
-    let downloaded_files = stream::iter(
-        urls.into_iter()
-            .unique()
-            .map(|url| download_file(client, url, &multi_progress)),
-    )
-    .buffer_unordered(concurrent_downloads.get() as usize)
-    .try_collect::<Vec<_>>()
-    .await?;
+    let results = stream::iter(urls.into_iter().map(|url| {
+        let multi_progress = &multi_progress;
+        let host_permits = &host_permits;
+        async move {
+            let _permit = match url.host_str().and_then(|host| host_permits.get(host)) {
+                Some(semaphore) => Some(semaphore.acquire().await?),
+                None => None,
+            };
+            download_file(client, url.clone(), multi_progress, options)
+                .await
+                .wrap_err_with(|| format!("无法下载 {url}"))
+        }
+    }))
+    .buffered(concurrent_downloads.get() as usize)
+    .collect::<Vec<_>>()
+    .await;
     multi_progress.clear()?;
+
+    let (downloaded_files, errors) = results
+        .into_iter()
+        .partition_result::<Vec<_>, Vec<_>, _, _>();
+    if !errors.is_empty() {
+        return Err(DownloadErrors(errors).into());
+    }
     Ok(downloaded_files)
 }
 
+/// A file that is larger than the maximum download size.
+#[derive(Debug, Error)]
+pub enum TooLargeError {
+    #[error(
+        "{url} 的大小 ({}) 超过了最大下载大小 ({})",
+        DecimalBytes(*size),
+        DecimalBytes(*max_size)
+    )]
+    ContentLength {
+        url: DecodedUrl,
+        size: u64,
+        max_size: u64,
+    },
+    /// The response had no `Content-Length`, so it was only found to be too large part way through
+    #[error("{url} 超过了最大下载大小 ({})", DecimalBytes(*max_size))]
+    Streamed { url: DecodedUrl, max_size: u64 },
+}
+
+/// Every download that failed, each with the URL it was for and why it failed.
+#[derive(Debug, Error)]
+pub struct DownloadErrors(Vec<color_eyre::Report>);
+
+impl DownloadErrors {
+    pub fn iter(&self) -> impl Iterator<Item = &color_eyre::Report> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for DownloadErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 个安装程序下载失败:", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  {error:#}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A local file to analyse in place of downloading the URL it will be published at.
+#[derive(Clone, Debug)]
+pub struct LocalFile {
+    url: DecodedUrl,
+    path: Utf8PathBuf,
+}
+
+impl FromStr for LocalFile {
+    type Err = color_eyre::Report;
+
+    /// Parses `<url>=<path>`, splitting on the last `=` as query strings often contain one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (url, path) = s
+            .rsplit_once('=')
+            .ok_or_else(|| eyre!("应为 <url>=<path> 格式"))?;
+        Ok(Self {
+            url: url.parse()?,
+            path: Utf8PathBuf::from(path),
+        })
+    }
+}
+
+#[derive(Args)]
+pub struct DownloadArgs {
+    /// 不使用下载缓存，始终重新下载安装程序
+    #[arg(long, env = "KOMAC_NO_CACHE")]
+    no_cache: bool,
+
+    /// 下载失败时的重试次数
+    #[arg(long, default_value_t = 3)]
+    retries: u8,
+
+    /// 允许下载的最大安装程序大小（以 MB 为单位）
+    #[arg(long, default_value_t = 4096)]
+    max_download_size: u64,
+
+    /// 允许分析的最大安装程序大小（以 MB 为单位），更大的安装程序只计算 SHA-256
+    #[arg(long, default_value_t = 2048)]
+    max_analysis_size: u64,
+
+    /// 即使无法解析出带版本号的 URL，也使用 GitHub、GitLab 或 SourceForge 上始终指向最新版本的安装程序
+    /// URL
+    #[arg(long)]
+    allow_vanity_url: bool,
+}
+
+impl DownloadArgs {
+    /// Opens the download cache unless it was disabled.
+    pub fn cache(&self) -> Option<DownloadCache> {
+        (!self.no_cache).then(DownloadCache::open).flatten()
+    }
+
+    pub const fn options<'cache>(
+        &self,
+        cache: Option<&'cache DownloadCache>,
+    ) -> DownloadOptions<'cache> {
+        DownloadOptions {
+            cache,
+            retries: self.retries,
+            max_size: self.max_download_size.saturating_mul(1_000_000),
+            max_analysis_size: self.max_analysis_size.saturating_mul(1_000_000),
+            allow_vanity_url: self.allow_vanity_url,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct LocalFiles {
+    /// 分析本地文件而不是下载 URL，格式为 <url>=<path>
+    #[arg(long = "file", value_name = "URL=PATH")]
+    files: Vec<LocalFile>,
+
+    /// 分析此目录中与 URL 文件名相同的文件，而不是下载它们
+    #[arg(long, value_hint = clap::ValueHint::DirPath)]
+    files_dir: Option<Utf8PathBuf>,
+
+    /// 下载本地文件对应的 URL，确认其 SHA-256 与本地文件一致
+    #[arg(long)]
+    verify_remote: bool,
+}
+
+impl LocalFiles {
+    /// Returns the local file to use for a URL, either given explicitly or found in the files
+    /// directory by the last segment of the URL's path.
+    fn path_for(&self, url: &DecodedUrl) -> Option<Utf8PathBuf> {
+        self.files
+            .iter()
+            .find(|local_file| local_file.url == *url)
+            .map(|local_file| local_file.path.clone())
+            .or_else(|| {
+                let files_dir = self.files_dir.as_deref()?;
+                let file_name = url
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|file_name| !file_name.is_empty())?;
+                Some(files_dir.join(file_name)).filter(|path| path.is_file())
+            })
+    }
+
+    /// Uses the local file for each URL that has one and downloads the rest.
+    ///
+    /// The local files are recorded under their URL, so the manifest points at where the file
+    /// will be published.
+    pub async fn fetch(
+        &self,
+        client: &Client,
+        urls: Vec<DecodedUrl>,
+        concurrent_downloads: NonZeroU8,
+        options: DownloadOptions<'_>,
+    ) -> Result<Vec<DownloadedFile>> {
+        for local_file in &self.files {
+            if !urls.contains(&local_file.url) {
+                bail!("{} 不是安装程序 URL 之一", local_file.url);
+            }
+        }
+
+        let (local, remote): (Vec<_>, Vec<_>) =
+            urls.into_iter()
+                .unique()
+                .partition_map(|url| match self.path_for(&url) {
+                    Some(path) => Either::Left((url, path)),
+                    None => Either::Right(url),
+                });
+
+        let local_files = local
+            .into_iter()
+            .map(|(url, path)| open_local_file(url, &path, options.max_analysis_size))
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.verify_remote && !local_files.is_empty() {
+            // Only the hashes of the remote files are compared, so there is no need to keep them
+            let remote_files = download_urls(
+                client,
+                local_files.iter().map(|file| file.url.clone()).collect(),
+                concurrent_downloads,
+                DownloadOptions {
+                    max_analysis_size: 0,
+                    ..options
+                },
+            )
+            .await?;
+            // The remote URLs may have been rewritten while downloading, so they are paired with
+            // the local files by position rather than by URL
+            if remote_files.len() != local_files.len() {
+                bail!(
+                    "只下载了 {} 个远程文件，但有 {} 个本地文件",
+                    remote_files.len(),
+                    local_files.len()
+                );
+            }
+            for (local_file, remote_file) in local_files.iter().zip(&remote_files) {
+                if remote_file.sha_256 != local_file.sha_256 {
+                    bail!(
+                        "{} 的 SHA-256 ({}) 与本地文件的 SHA-256 ({}) 不一致",
+                        local_file.url,
+                        remote_file.sha_256,
+                        local_file.sha_256
+                    );
+                }
+            }
+            info!("所有本地文件都与远程文件一致");
+        }
+
+        let mut files = download_urls(client, remote, concurrent_downloads, options).await?;
+        files.extend(local_files);
+        Ok(files)
+    }
+}
+
+fn open_local_file(
+    url: DecodedUrl,
+    path: &Utf8Path,
+    max_analysis_size: u64,
+) -> Result<DownloadedFile> {
+    let mut file = File::open(path).wrap_err_with(|| format!("无法打开 {path}"))?;
+    let mut hasher = Sha256::new();
+    let payload = if file.metadata()?.len() > max_analysis_size {
+        info!(
+            "{path} 的大小超过了最大分析大小 ({})，只计算其 SHA-256",
+            DecimalBytes(max_analysis_size)
+        );
+        io::copy(&mut file, &mut hasher)?;
+        Payload::Skipped
+    } else {
+        let payload = Payload::map(file)?;
+        hasher.update(payload.data().unwrap_or_default());
+        payload
+    };
+    let sha_256 = Sha256String::from_hasher(&hasher.finalize())?;
+    info!("使用 {path} 代替 {url}");
+    Ok(DownloadedFile {
+        file_name: path.file_name().unwrap_or(path.as_str()).to_owned(),
+        url,
+        payload,
+        sha_256,
+        last_modified: None,
+        app_installer: None,
+    })
+}
+
 pub struct DownloadedFile {
-    // 由于下载的文件是临时文件，因此将其存储在这里，以便引用保持
-    // 文件不会被删除。这是必要的，因为内存映射需要
-    // 文件保持存在。
-    #[expect(dead_code)]
-    file: File,
     url: DecodedUrl,
-    mmap: Mmap,
+    payload: Payload,
     sha_256: Sha256String,
     file_name: String,
     last_modified: Option<NaiveDate>,
+    app_installer: Option<AppInstaller>,
+}
+
+impl DownloadedFile {
+    pub const fn url(&self) -> &DecodedUrl {
+        &self.url
+    }
+
+    pub const fn sha_256(&self) -> &Sha256String {
+        &self.sha_256
+    }
 }
 
 pub async fn process_files(
@@ -191,13 +783,43 @@ pub async fn process_files(
     stream::iter(files.iter_mut().map(
         |DownloadedFile {
              url,
-             mmap,
+             payload,
              sha_256,
              file_name,
              last_modified,
+             app_installer,
              ..
          }| async move {
-            let mut file_analyser = FileAnalyser::new(mmap, file_name)?;
+            let mut file_analyser = match payload.data() {
+                Some(data) => FileAnalyser::new(data, file_name)?,
+                None => {
+                    let file_analyser = FileAnalyser::hash_only(file_name);
+                    // These are read from the package itself, so nothing else can provide them
+                    if file_analyser.installers.iter().any(|installer| {
+                        matches!(
+                            installer.r#type,
+                            Some(InstallerType::Msix | InstallerType::Appx)
+                        )
+                    }) {
+                        warn!(
+                            "{file_name} 超过了最大分析大小，清单中将缺少 SignatureSha256 和 PackageFamilyName，请手动填写"
+                        );
+                    }
+                    file_analyser
+                }
+            };
+            // Many CDNs omit or rewrite Last-Modified, so fall back to dates inside the file
+            if let Some(last_modified) = *last_modified {
+                file_analyser.release_date = Some(ReleaseDate::new(
+                    last_modified,
+                    ReleaseDateSource::LastModified,
+                ));
+            } else if let Some(release_date) = file_analyser.release_date {
+                info!(
+                    "{url} 没有 Last-Modified 响应头，发布日期取自{}",
+                    release_date.source
+                );
+            }
             let architecture_in_url = Architecture::from_url(url.as_str());
             for installer in &mut file_analyser.installers {
                 if let Some(architecture) = architecture_in_url {
@@ -205,9 +827,12 @@ pub async fn process_files(
                 }
                 installer.url = url.clone();
                 installer.sha_256 = sha_256.clone();
-                installer.release_date = *last_modified;
+                installer.release_date = file_analyser
+                    .release_date
+                    .map(|release_date| release_date.date);
             }
             file_analyser.file_name = mem::take(file_name);
+            file_analyser.app_installer = app_installer.take();
             Ok((mem::take(url), file_analyser))
         },
     ))
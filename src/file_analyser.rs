@@ -1,9 +1,13 @@
-use std::{io::Cursor, mem};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Cursor,
+    mem,
+};
 
 use camino::Utf8Path;
 use color_eyre::eyre::{Result, bail};
-use memmap2::Mmap;
-use tracing::debug;
+use itertools::Itertools;
+use tracing::{debug, warn};
 use winget_types::{
     installer::{Architecture, Installer, InstallerType},
     locale::{Copyright, PackageName, Publisher},
@@ -12,12 +16,16 @@ use yara_x::mods::PE;
 
 use crate::{
     installers::{
+        app_installer::AppInstaller,
         burn::{Burn, BurnError},
+        exe_framework::ExeFramework,
         inno::{Inno, InnoError},
         msi::Msi,
         msix_family::{Msix, bundle::MsixBundle},
         nsis::{Nsis, NsisError},
         possible_installers::PossibleInstaller,
+        squirrel::{Squirrel, SquirrelError},
+        utils::{authenticode, release_date::ReleaseDate, runtime_dependencies, signer::Signer},
         zip::Zip,
     },
     traits::{FromMachine, FromVSVersionInfo},
@@ -30,8 +38,10 @@ pub const APPX: &str = "appx";
 pub const MSIX_BUNDLE: &str = "msixbundle";
 pub const APPX_BUNDLE: &str = "appxbundle";
 pub const ZIP: &str = "zip";
+pub const APP_INSTALLER: &str = "appinstaller";
 
 const ORIGINAL_FILENAME: &str = "OriginalFilename";
+const INTERNAL_NAME: &str = "InternalName";
 const FILE_DESCRIPTION: &str = "FileDescription";
 const BASIC_INSTALLER_KEYWORDS: [&str; 4] = ["installer", "setup", "7zs.sfx", "7zsd.sfx"];
 
@@ -40,12 +50,16 @@ pub struct FileAnalyser<'data> {
     pub copyright: Option<Copyright>,
     pub package_name: Option<PackageName>,
     pub publisher: Option<Publisher>,
+    pub command_alias: Option<String>,
+    pub signer: Option<Signer>,
+    pub app_installer: Option<AppInstaller>,
+    pub release_date: Option<ReleaseDate>,
     pub installers: Vec<Installer>,
     pub zip: Option<Zip<Cursor<&'data [u8]>>>,
 }
 
 impl<'data> FileAnalyser<'data> {
-    pub fn new(data: &'data Mmap, file_name: &str) -> Result<Self> {
+    pub fn new(data: &'data [u8], file_name: &str) -> Result<Self> {
         let extension = Utf8Path::new(file_name)
             .extension()
             .unwrap_or_default()
@@ -54,53 +68,92 @@ impl<'data> FileAnalyser<'data> {
         let mut copyright = None;
         let mut package_name = None;
         let mut publisher = None;
+        let mut command_alias = None;
+        let mut runtime_dependencies = BTreeSet::new();
+        let mut windows_features = BTreeSet::new();
+        let mut signer = None;
+        let mut app_installer = None;
+        let mut release_date = None;
         let installer = match extension.as_str() {
-            MSI => PossibleInstaller::Msi(Msi::new(Cursor::new(data.as_ref()))?),
-            MSIX | APPX => PossibleInstaller::Msix(Msix::new(Cursor::new(data.as_ref()))?),
+            MSI => {
+                let msi = Msi::new(Cursor::new(data))?;
+                release_date = msi.release_date;
+                PossibleInstaller::Msi(msi)
+            }
+            MSIX | APPX => {
+                let mut msix = Msix::new(Cursor::new(data))?;
+                signer = msix.signer.take();
+                release_date = msix.release_date;
+                PossibleInstaller::Msix(msix)
+            }
             MSIX_BUNDLE | APPX_BUNDLE => {
-                PossibleInstaller::MsixBundle(MsixBundle::new(Cursor::new(data.as_ref()))?)
+                let mut msix_bundle = MsixBundle::new(Cursor::new(data))?;
+                signer = msix_bundle.signer.take();
+                release_date = msix_bundle.release_date;
+                PossibleInstaller::MsixBundle(msix_bundle)
+            }
+            APP_INSTALLER => {
+                let scoped_app_installer = AppInstaller::new(data)?;
+                let installer = PossibleInstaller::Other(scoped_app_installer.installer());
+                app_installer = Some(scoped_app_installer);
+                installer
             }
             ZIP => {
-                let mut scoped_zip = Zip::new(Cursor::new(data.as_ref()))?;
+                let mut scoped_zip = Zip::new(Cursor::new(data))?;
                 let installer = PossibleInstaller::Zip(mem::take(&mut scoped_zip.installers));
+                release_date = scoped_zip.release_date;
                 zip = Some(scoped_zip);
                 installer
             }
             EXE => {
-                let pe = yara_x::mods::invoke::<PE>(data.as_ref()).unwrap();
+                let pe = yara_x::mods::invoke::<PE>(data).unwrap();
                 debug!(?pe.version_info);
                 copyright = Copyright::from_version_info(&pe.version_info);
                 package_name = PackageName::from_version_info(&pe.version_info);
                 publisher = Publisher::from_version_info(&pe.version_info);
-                match Burn::new(data.as_ref(), &pe) {
+                command_alias = get_command_alias(&pe.version_info);
+                runtime_dependencies = runtime_dependencies::from_imports(&pe);
+                windows_features = runtime_dependencies::from_clr_header(data, &pe);
+                signer = authenticode::read_signer(&pe);
+                release_date = ReleaseDate::from_pe(&pe);
+                match (&signer, &publisher) {
+                    (Some(signer), Some(publisher))
+                        if !signer.is_publisher(&publisher.to_string()) =>
+                    {
+                        warn!(
+                            "签名者 \"{}\" 与版本信息中的发布者 \"{publisher}\" 不一致",
+                            signer.subject
+                        );
+                    }
+                    _ => {}
+                }
+                match Burn::new(data, &pe) {
                     Ok(burn) => PossibleInstaller::Burn(burn),
-                    Err(BurnError::NotBurnFile) => match Nsis::new(data.as_ref(), &pe) {
+                    Err(BurnError::NotBurnFile) => match Nsis::new(data, &pe) {
                         Ok(nsis_file) => PossibleInstaller::Nsis(nsis_file),
-                        Err(NsisError::NotNsisFile) => match Inno::new(data.as_ref(), &pe) {
+                        Err(NsisError::NotNsisFile) => match Inno::new(data, &pe) {
                             Ok(inno_file) => PossibleInstaller::Inno(inno_file),
-                            Err(InnoError::NotInnoFile) => PossibleInstaller::Other(Installer {
-                                architecture: Architecture::from_machine(pe.machine()),
-                                r#type: pe
-                                    .version_info_list
-                                    .iter()
-                                    .filter(|key_value| {
-                                        matches!(
-                                            key_value.key(),
-                                            FILE_DESCRIPTION | ORIGINAL_FILENAME
-                                        )
-                                    })
-                                    .filter_map(|key_value| {
-                                        key_value.value.as_deref().map(str::to_ascii_lowercase)
-                                    })
-                                    .any(|value| {
-                                        BASIC_INSTALLER_KEYWORDS
-                                            .iter()
-                                            .any(|keyword| value.contains(keyword))
-                                    })
-                                    .then_some(InstallerType::Exe)
-                                    .or(Some(InstallerType::Portable)),
-                                ..Installer::default()
-                            }),
+                            Err(InnoError::NotInnoFile) => match Squirrel::new(data, &pe) {
+                                Ok(squirrel) => {
+                                    package_name = package_name.or_else(|| {
+                                        squirrel
+                                            .title
+                                            .as_deref()
+                                            .and_then(|title| PackageName::new(title).ok())
+                                    });
+                                    publisher = publisher.or_else(|| {
+                                        squirrel
+                                            .authors
+                                            .as_deref()
+                                            .and_then(|authors| Publisher::new(authors).ok())
+                                    });
+                                    PossibleInstaller::Squirrel(squirrel)
+                                }
+                                Err(SquirrelError::NotSquirrelFile) => {
+                                    PossibleInstaller::Other(other_exe_installer(data, &pe))
+                                }
+                                Err(squirrel_error) => return Err(squirrel_error.into()),
+                            },
                             Err(inno_error) => return Err(inno_error.into()),
                         },
                         Err(nsis_error) => return Err(nsis_error.into()),
@@ -110,13 +163,144 @@ impl<'data> FileAnalyser<'data> {
             }
             _ => bail!(r#"不支持的文件扩展名: "{extension}""#),
         };
+        let mut installers = installer.installers();
+        for installer in installers.iter_mut().filter(|installer| {
+            matches!(
+                installer.r#type,
+                Some(InstallerType::Exe | InstallerType::Portable)
+            )
+        }) {
+            // A managed installer can't run without its runtime either, but the libraries an
+            // installer imports are only needed by itself and not by the application it installs
+            runtime_dependencies::insert_windows_features(installer, windows_features.clone());
+            if installer.r#type == Some(InstallerType::Portable) {
+                runtime_dependencies::insert(installer, runtime_dependencies.clone());
+            }
+        }
         Ok(Self {
-            installers: installer.installers(),
+            installers,
             file_name: String::new(),
             copyright,
             package_name,
             publisher,
+            command_alias,
+            signer,
+            app_installer,
+            release_date,
             zip,
         })
     }
+
+    /// Creates an analyser for a file that was too large to analyse, with a single installer
+    /// whose type is inferred from the file extension where it is unambiguous. EXEs are left
+    /// without a type, which is taken from the previous manifest or asked for instead.
+    pub fn hash_only(file_name: &str) -> Self {
+        let extension = Utf8Path::new(file_name)
+            .extension()
+            .unwrap_or_default()
+            .to_lowercase();
+        let r#type = match extension.as_str() {
+            MSI => Some(InstallerType::Msi),
+            MSIX | MSIX_BUNDLE => Some(InstallerType::Msix),
+            APPX | APPX_BUNDLE => Some(InstallerType::Appx),
+            ZIP => Some(InstallerType::Zip),
+            _ => None,
+        };
+        Self {
+            file_name: String::new(),
+            copyright: None,
+            package_name: None,
+            publisher: None,
+            command_alias: None,
+            signer: None,
+            app_installer: None,
+            release_date: None,
+            installers: vec![Installer {
+                r#type,
+                ..Installer::default()
+            }],
+            zip: None,
+        }
+    }
+}
+
+/// Creates an installer for an EXE that is not one of the installer types that can be read in
+/// full, using the silent switches of its framework if it is recognised.
+fn other_exe_installer(data: &[u8], pe: &PE) -> Installer {
+    if let Some(framework) = ExeFramework::new(data, pe) {
+        debug!(%framework);
+        return framework.installer(pe);
+    }
+
+    Installer {
+        architecture: Architecture::from_machine(pe.machine()),
+        r#type: pe
+            .version_info_list
+            .iter()
+            .filter(|key_value| matches!(key_value.key(), FILE_DESCRIPTION | ORIGINAL_FILENAME))
+            .filter_map(|key_value| key_value.value.as_deref().map(str::to_ascii_lowercase))
+            .any(|value| {
+                BASIC_INSTALLER_KEYWORDS
+                    .iter()
+                    .any(|keyword| value.contains(keyword))
+            })
+            .then_some(InstallerType::Exe)
+            .or(Some(InstallerType::Portable)),
+        ..Installer::default()
+    }
+}
+
+/// Infers the command a portable executable would be invoked with from its version info.
+///
+/// Only the original file name and internal name are used, as they are what the developer
+/// intended the executable to be called. The product name is usually a display name that isn't
+/// suitable as a command.
+fn get_command_alias(version_info: &HashMap<String, String>) -> Option<String> {
+    [ORIGINAL_FILENAME, INTERNAL_NAME]
+        .iter()
+        .filter_map(|key| version_info.get(*key))
+        .find_map(|value| to_command_alias(value))
+}
+
+/// Turns a file or package name into a lowercase command, returning `None` if it contains
+/// characters that wouldn't be typed as part of a command.
+pub fn to_command_alias(name: &str) -> Option<String> {
+    let path = Utf8Path::new(name.trim());
+    let stem = if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(EXE))
+    {
+        path.file_stem().unwrap_or_default()
+    } else {
+        path.as_str()
+    };
+    let alias = stem.split_whitespace().join("-").to_lowercase();
+    (!alias.is_empty()
+        && alias
+            .chars()
+            .all(|char| char.is_alphanumeric() || matches!(char, '-' | '_' | '.')))
+    .then_some(alias)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use crate::file_analyser::get_command_alias;
+
+    #[rstest]
+    #[case(&[("OriginalFilename", "Example.exe")], Some("example"))]
+    #[case(&[("InternalName", "example-cli"), ("ProductName", "Example")], Some("example-cli"))]
+    #[case(&[("OriginalFilename", "  "), ("InternalName", "Example Tool")], Some("example-tool"))]
+    #[case(&[("ProductName", "Example")], None)]
+    #[case(&[], None)]
+    fn command_alias(#[case] version_info: &[(&str, &str)], #[case] expected: Option<&str>) {
+        let version_info = version_info
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(get_command_alias(&version_info).as_deref(), expected);
+    }
 }
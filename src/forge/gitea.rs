@@ -0,0 +1,246 @@
+use std::collections::BTreeSet;
+
+use color_eyre::eyre::Result;
+use itertools::Itertools;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use winget_types::{
+    locale::{License, ReleaseNotes, Tag},
+    shared::url::{
+        DecodedUrl, LicenseUrl, PackageUrl, PublisherSupportUrl, PublisherUrl, ReleaseNotesUrl,
+    },
+};
+
+use crate::{
+    forge::{MetadataProvider, Release, decode_tag, instance_root, is_license_file},
+    github::{
+        github_client::GitHubValues,
+        graphql::{get_all_values::ReleaseAsset, types::Html},
+    },
+    traits::FromHtml,
+};
+
+/// Public instances of Gitea and its fork Forgejo. Self-hosted instances can't be told apart
+/// from any other website serving downloads under a similar path, so they are not queried.
+const GITEA_HOSTS: [&str; 2] = ["codeberg.org", "gitea.com"];
+
+/// Releases on Gitea, Forgejo and Codeberg.
+#[derive(Clone, Copy)]
+pub struct Gitea<'a>(&'a Client);
+
+impl<'a> Gitea<'a> {
+    pub const fn new(client: &'a Client) -> Self {
+        Self(client)
+    }
+}
+
+/// <https://gitea.com/api/swagger#/repository/repoGet>
+#[derive(Deserialize)]
+struct Repository {
+    html_url: Url,
+    #[serde(default)]
+    has_issues: bool,
+    #[serde(default)]
+    topics: Vec<String>,
+    /// SPDX identifiers of the licenses detected in the repository, which requires Gitea 1.22
+    #[serde(default)]
+    licenses: Vec<String>,
+}
+
+/// <https://gitea.com/api/swagger#/repository/repoGetReleaseByTag>
+#[derive(Deserialize)]
+struct GiteaRelease {
+    body: String,
+    html_url: Url,
+    assets: Vec<Attachment>,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    name: String,
+    browser_download_url: Url,
+}
+
+/// <https://gitea.com/api/swagger#/repository/repoGetContentsList>
+#[derive(Deserialize)]
+struct ContentsEntry {
+    name: String,
+    r#type: String,
+    html_url: Option<Url>,
+}
+
+/// <https://gitea.com/api/swagger#/miscellaneous/renderMarkdown>
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct MarkdownOption<'a> {
+    text: &'a str,
+    mode: &'a str,
+    context: &'a str,
+}
+
+impl MetadataProvider for Gitea<'_> {
+    /// Matches release downloads such as
+    /// `https://codeberg.org/owner/repo/releases/download/v1.0.0/setup.exe`.
+    fn release(&self, url: &DecodedUrl) -> Option<Release> {
+        if !GITEA_HOSTS.contains(&url.host_str()?) {
+            return None;
+        }
+        let mut parts = url.path_segments()?;
+        let _file_name = parts.next_back()?;
+        let owner = parts.next()?.to_owned();
+        let repo = parts.next()?.to_owned();
+        if parts.next()? != "releases" || parts.next()? != "download" {
+            return None;
+        }
+        Some(Release {
+            base: instance_root(url)?,
+            owner,
+            repo,
+            tag: decode_tag(&parts.join("/")),
+        })
+    }
+
+    async fn get_values(&self, release: Release) -> Result<GitHubValues> {
+        let Release {
+            owner, repo, tag, ..
+        } = &release;
+
+        let repository = self
+            .0
+            .get(release.api_url(&["api", "v1", "repos", owner, repo]))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Repository>()
+            .await?;
+
+        let response = self
+            .0
+            .get(release.api_url(&["api", "v1", "repos", owner, repo, "releases", "tags", tag]))
+            .send()
+            .await?;
+        let gitea_release = if response.status() == StatusCode::NOT_FOUND {
+            None
+        } else {
+            Some(response.error_for_status()?.json::<GiteaRelease>().await?)
+        };
+
+        let license_url = self
+            .0
+            .get(release.api_url(&["api", "v1", "repos", owner, repo, "contents"]))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<ContentsEntry>>()
+            .await?
+            .into_iter()
+            .find(|entry| entry.r#type == "file" && is_license_file(&entry.name))
+            .and_then(|entry| entry.html_url)
+            .and_then(|url| url.as_str().parse::<LicenseUrl>().ok());
+
+        // Gitea only returns release notes as Markdown, so have it render them to HTML in the
+        // same way as the notes are shown on the release page
+        let release_notes = match gitea_release
+            .as_ref()
+            .filter(|release| !release.body.trim().is_empty())
+        {
+            Some(gitea_release) => {
+                let html = self
+                    .0
+                    .post(release.api_url(&["api", "v1", "markdown"]))
+                    .json(&MarkdownOption {
+                        text: &gitea_release.body,
+                        mode: "gfm",
+                        context: repository.html_url.as_str(),
+                    })
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+                ReleaseNotes::from_html(&Html::new(html))
+            }
+            None => None,
+        };
+
+        let topics = repository
+            .topics
+            .into_iter()
+            .flat_map(Tag::new)
+            .collect::<BTreeSet<_>>();
+
+        let publisher_support_url = if repository.has_issues {
+            format!("{}/issues", repository.html_url)
+                .parse::<PublisherSupportUrl>()
+                .ok()
+        } else {
+            None
+        };
+
+        Ok(GitHubValues {
+            publisher_url: release.api_url(&[owner]).as_str().parse::<PublisherUrl>()?,
+            publisher_support_url,
+            license: repository
+                .licenses
+                .into_iter()
+                .exactly_one()
+                .ok()
+                .and_then(|license| License::new(license).ok()),
+            license_url,
+            package_url: repository.html_url.as_str().parse::<PackageUrl>()?,
+            release_notes,
+            release_notes_url: gitea_release
+                .as_ref()
+                .and_then(|release| release.html_url.as_str().parse::<ReleaseNotesUrl>().ok()),
+            topics: Option::from(topics).filter(|topics| !topics.is_empty()),
+            release_assets: gitea_release
+                .map(|release| {
+                    release
+                        .assets
+                        .into_iter()
+                        .map(|attachment| ReleaseAsset {
+                            name: attachment.name,
+                            download_url: attachment.browser_download_url,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use winget_types::shared::url::DecodedUrl;
+
+    use crate::forge::{MetadataProvider, gitea::Gitea};
+
+    #[rstest]
+    #[case(
+        "https://codeberg.org/owner/repo/releases/download/v1.0.0/setup.exe",
+        Some(("owner", "repo", "v1.0.0"))
+    )]
+    #[case(
+        "https://gitea.com/owner/repo/releases/download/release%2F2.0/setup.msi",
+        Some(("owner", "repo", "release/2.0"))
+    )]
+    #[case("https://codeberg.org/owner/repo/archive/v1.0.0.zip", None)]
+    #[case(
+        "https://example.com/owner/repo/releases/download/v1.0.0/setup.exe",
+        None
+    )]
+    fn release(#[case] url: &str, #[case] expected: Option<(&str, &str, &str)>) {
+        let client = reqwest::Client::new();
+        let release = Gitea::new(&client).release(&url.parse::<DecodedUrl>().unwrap());
+        assert_eq!(
+            release.as_ref().map(|release| (
+                release.owner.as_str(),
+                release.repo.as_str(),
+                release.tag.as_str()
+            )),
+            expected
+        );
+    }
+}
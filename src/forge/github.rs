@@ -0,0 +1,38 @@
+use color_eyre::eyre::Result;
+use itertools::Itertools;
+use winget_types::shared::url::DecodedUrl;
+
+use crate::{
+    forge::{MetadataProvider, Release, instance_root},
+    github::github_client::{GitHub, GitHubValues},
+};
+
+impl MetadataProvider for GitHub {
+    fn release(&self, url: &DecodedUrl) -> Option<Release> {
+        if url.host_str() != Some(self.host()) {
+            return None;
+        }
+        let mut parts = url.path_segments()?;
+        let _file_name = parts.next_back()?;
+        let owner = parts.next()?.to_owned();
+        let repo = parts.next()?.to_owned();
+        let _releases = parts.next()?;
+        let _download = parts.next()?;
+        Some(Release {
+            base: instance_root(url)?,
+            owner,
+            repo,
+            tag: parts.join("/"),
+        })
+    }
+
+    async fn get_values(&self, release: Release) -> Result<GitHubValues> {
+        Ok(self
+            .get_all_values()
+            .owner(release.owner)
+            .repo(release.repo)
+            .tag_name(release.tag)
+            .send()
+            .await?)
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeSet;
+
+use color_eyre::eyre::Result;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use url::Url;
+use winget_types::{
+    locale::{License, ReleaseNotes, Tag},
+    shared::url::{
+        DecodedUrl, LicenseUrl, PackageUrl, PublisherSupportUrl, PublisherUrl, ReleaseNotesUrl,
+    },
+};
+
+use crate::{
+    forge::{MetadataProvider, Release, decode_tag, instance_root},
+    github::{
+        github_client::GitHubValues,
+        graphql::{get_all_values::ReleaseAsset, types::Html},
+    },
+    traits::FromHtml,
+};
+
+/// GitLab separates the path of a project from the pages within it with this segment.
+const PROJECT_SEPARATOR: &str = "-";
+
+/// The license key GitLab uses when it cannot identify a license.
+const OTHER_LICENSE: &str = "other";
+
+/// Releases on gitlab.com or a self-managed GitLab instance.
+#[derive(Clone, Copy)]
+pub struct GitLab<'a>(&'a Client);
+
+impl<'a> GitLab<'a> {
+    pub const fn new(client: &'a Client) -> Self {
+        Self(client)
+    }
+}
+
+/// <https://docs.gitlab.com/api/projects/#get-a-single-project>
+#[derive(Deserialize)]
+struct Project {
+    web_url: Url,
+    #[serde(default)]
+    topics: Vec<String>,
+    license_url: Option<Url>,
+    license: Option<ProjectLicense>,
+    #[serde(default)]
+    issues_enabled: bool,
+    namespace: Namespace,
+}
+
+#[derive(Deserialize)]
+struct ProjectLicense {
+    key: String,
+}
+
+#[derive(Deserialize)]
+struct Namespace {
+    web_url: Url,
+}
+
+/// <https://docs.gitlab.com/api/releases/#get-a-release-by-a-tag-name>
+#[derive(Deserialize)]
+struct GitLabRelease {
+    description_html: Option<String>,
+    #[serde(rename = "_links")]
+    links: ReleaseLinks,
+    assets: ReleaseAssets,
+}
+
+#[derive(Deserialize)]
+struct ReleaseLinks {
+    #[serde(rename = "self")]
+    release: Url,
+}
+
+#[derive(Deserialize)]
+struct ReleaseAssets {
+    links: Vec<AssetLink>,
+}
+
+#[derive(Deserialize)]
+struct AssetLink {
+    name: String,
+    url: Url,
+}
+
+impl MetadataProvider for GitLab<'_> {
+    /// Matches release downloads such as
+    /// `https://gitlab.com/group/project/-/releases/v1.0.0/downloads/setup.exe` and source
+    /// archives such as `https://gitlab.com/group/project/-/archive/v1.0.0/project-v1.0.0.zip`.
+    fn release(&self, url: &DecodedUrl) -> Option<Release> {
+        let segments = url.path_segments()?.collect::<Vec<_>>();
+        let separator = segments
+            .iter()
+            .position(|&segment| segment == PROJECT_SEPARATOR)?;
+        let (project, page) = segments.split_at(separator);
+        let tag = match page {
+            [_, "releases", tag, "downloads", ..] | [_, "archive", tag, _] => tag,
+            _ => return None,
+        };
+        let (repo, owner) = project.split_last()?;
+        if owner.is_empty() {
+            return None;
+        }
+        Some(Release {
+            base: instance_root(url)?,
+            owner: owner.join("/"),
+            repo: (*repo).to_owned(),
+            tag: decode_tag(tag),
+        })
+    }
+
+    async fn get_values(&self, release: Release) -> Result<GitHubValues> {
+        let project_id = format!("{}/{}", release.owner, release.repo);
+
+        let mut project_url = release.api_url(&["api", "v4", "projects", &project_id]);
+        project_url.set_query(Some("license=true"));
+        let project = self
+            .0
+            .get(project_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Project>()
+            .await?;
+
+        let mut release_url = release.api_url(&[
+            "api",
+            "v4",
+            "projects",
+            &project_id,
+            "releases",
+            &release.tag,
+        ]);
+        release_url.set_query(Some("include_html_description=true"));
+        let response = self.0.get(release_url).send().await?;
+        let gitlab_release = if response.status() == StatusCode::NOT_FOUND {
+            None
+        } else {
+            Some(response.error_for_status()?.json::<GitLabRelease>().await?)
+        };
+
+        let topics = project
+            .topics
+            .into_iter()
+            .flat_map(Tag::new)
+            .collect::<BTreeSet<_>>();
+
+        let publisher_support_url = if project.issues_enabled {
+            format!("{}/-/issues", project.web_url)
+                .parse::<PublisherSupportUrl>()
+                .ok()
+        } else {
+            None
+        };
+
+        Ok(GitHubValues {
+            publisher_url: project.namespace.web_url.as_str().parse::<PublisherUrl>()?,
+            publisher_support_url,
+            license: project
+                .license
+                .map(|license| license.key)
+                .filter(|key| key != OTHER_LICENSE)
+                .and_then(|key| License::new(key.to_ascii_uppercase()).ok()),
+            license_url: project
+                .license_url
+                .and_then(|url| url.as_str().parse::<LicenseUrl>().ok()),
+            package_url: project.web_url.as_str().parse::<PackageUrl>()?,
+            release_notes: gitlab_release
+                .as_ref()
+                .and_then(|release| release.description_html.clone())
+                .and_then(|html| ReleaseNotes::from_html(&Html::new(html))),
+            release_notes_url: gitlab_release.as_ref().and_then(|release| {
+                release
+                    .links
+                    .release
+                    .as_str()
+                    .parse::<ReleaseNotesUrl>()
+                    .ok()
+            }),
+            topics: Option::from(topics).filter(|topics| !topics.is_empty()),
+            release_assets: gitlab_release
+                .map(|release| {
+                    release
+                        .assets
+                        .links
+                        .into_iter()
+                        .map(|link| ReleaseAsset {
+                            name: link.name,
+                            download_url: link.url,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use winget_types::shared::url::DecodedUrl;
+
+    use crate::forge::{MetadataProvider, gitlab::GitLab};
+
+    #[rstest]
+    #[case(
+        "https://gitlab.com/owner/project/-/releases/v1.0.0/downloads/setup.exe",
+        Some(("owner", "project", "v1.0.0"))
+    )]
+    #[case(
+        "https://gitlab.example.com/group/subgroup/project/-/archive/1.0/project-1.0.zip",
+        Some(("group/subgroup", "project", "1.0"))
+    )]
+    #[case("https://gitlab.com/owner/project/-/raw/main/setup.exe", None)]
+    #[case("https://example.com/downloads/setup.exe", None)]
+    fn release(#[case] url: &str, #[case] expected: Option<(&str, &str, &str)>) {
+        let client = reqwest::Client::new();
+        let release = GitLab::new(&client).release(&url.parse::<DecodedUrl>().unwrap());
+        assert_eq!(
+            release.as_ref().map(|release| (
+                release.owner.as_str(),
+                release.repo.as_str(),
+                release.tag.as_str()
+            )),
+            expected
+        );
+    }
+}
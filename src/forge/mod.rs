@@ -0,0 +1,142 @@
+mod gitea;
+mod github;
+mod gitlab;
+
+use color_eyre::eyre::Result;
+use percent_encoding::percent_decode_str;
+use reqwest::Client;
+use tracing::warn;
+use url::Url;
+use winget_types::shared::url::DecodedUrl;
+
+use crate::{
+    forge::{gitea::Gitea, gitlab::GitLab},
+    github::github_client::{GitHub, GitHubValues},
+};
+
+/// A release on a code forge that an installer was published in.
+pub struct Release {
+    /// The root of the forge instance, such as `https://gitlab.com/`
+    pub base: Url,
+    /// The owner of the project, which on GitLab may be a path of nested groups
+    pub owner: String,
+    pub repo: String,
+    pub tag: String,
+}
+
+impl Release {
+    /// Returns the URL of an API endpoint on the forge instance, percent-encoding each segment.
+    fn api_url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base.clone();
+        url.path_segments_mut().unwrap().clear().extend(segments);
+        url
+    }
+}
+
+/// A code forge that can describe the project and release that an installer was published in,
+/// so that the locale manifests can be filled in from them.
+pub trait MetadataProvider {
+    /// Returns the release that an installer URL was published in, if the forge hosts it.
+    fn release(&self, url: &DecodedUrl) -> Option<Release>;
+
+    async fn get_values(&self, release: Release) -> Result<GitHubValues>;
+}
+
+#[derive(Clone, Copy)]
+enum Provider<'a> {
+    GitHub(&'a GitHub),
+    GitLab(GitLab<'a>),
+    Gitea(Gitea<'a>),
+}
+
+impl MetadataProvider for Provider<'_> {
+    fn release(&self, url: &DecodedUrl) -> Option<Release> {
+        match self {
+            Self::GitHub(github) => github.release(url),
+            Self::GitLab(gitlab) => gitlab.release(url),
+            Self::Gitea(gitea) => gitea.release(url),
+        }
+    }
+
+    async fn get_values(&self, release: Release) -> Result<GitHubValues> {
+        match self {
+            Self::GitHub(github) => github.get_values(release).await,
+            Self::GitLab(gitlab) => gitlab.get_values(release).await,
+            Self::Gitea(gitea) => gitea.get_values(release).await,
+        }
+    }
+}
+
+/// Starts fetching the project metadata of the first installer URL that was published on a
+/// supported forge.
+///
+/// GitHub is only queried when a GitHub client is available, whereas GitLab and Gitea are queried
+/// anonymously through their REST APIs. As those instances may be unreachable or rate limit
+/// anonymous requests, their failures are logged and the metadata is left out instead.
+pub fn get_release_values<'a>(
+    urls: &[DecodedUrl],
+    github: Option<&'a GitHub>,
+    client: &'a Client,
+) -> Option<impl Future<Output = Result<Option<GitHubValues>>> + use<'a>> {
+    let providers = github
+        .map(Provider::GitHub)
+        .into_iter()
+        .chain([
+            Provider::GitLab(GitLab::new(client)),
+            Provider::Gitea(Gitea::new(client)),
+        ])
+        .collect::<Vec<_>>();
+
+    let (provider, release) = urls.iter().find_map(|url| {
+        providers
+            .iter()
+            .find_map(|provider| Some((*provider, provider.release(url)?)))
+    })?;
+
+    Some(async move {
+        let base = release.base.clone();
+        match provider.get_values(release).await {
+            Ok(values) => Ok(Some(values)),
+            Err(error) if !matches!(provider, Provider::GitHub(_)) => {
+                warn!("无法从 {base} 获取发布信息: {error}");
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    })
+}
+
+/// Returns the origin of a URL as the root of the forge instance it belongs to.
+fn instance_root(url: &DecodedUrl) -> Option<Url> {
+    Url::parse(&url.origin().ascii_serialization()).ok()
+}
+
+/// Decodes a tag name taken from the path of a URL.
+fn decode_tag(tag: &str) -> String {
+    percent_decode_str(tag).decode_utf8_lossy().into_owned()
+}
+
+/// Returns whether a file in the root of a repository is its license, such as `LICENSE` or
+/// `license.md`.
+pub fn is_license_file(name: &str) -> bool {
+    name.rfind('.')
+        .map_or(name, |dot_index| &name[..dot_index])
+        .eq_ignore_ascii_case("license")
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::forge::is_license_file;
+
+    #[rstest]
+    #[case("LICENSE", true)]
+    #[case("license.md", true)]
+    #[case("License.txt", true)]
+    #[case("LICENSES", false)]
+    #[case("README.md", false)]
+    fn license_file(#[case] name: &str, #[case] expected: bool) {
+        assert_eq!(is_license_file(name), expected);
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::OnceLock;
+
+use clap::Args;
+use color_eyre::eyre::{Result, eyre};
+use url::Url;
+
+static GITHUB_API: OnceLock<GitHubApi> = OnceLock::new();
+
+const GITHUB_API_URL: &str = "https://api.github.com/";
+
+/// The GitHub instance that Komac talks to.
+#[derive(Args)]
+pub struct GitHubApiArgs {
+    /// GitHub REST API 的基础 URL，用于 GitHub Enterprise Server，例如 https://github.example.com/api/v3
+    #[arg(long, global = true, env = "KOMAC_GITHUB_API_URL", value_hint = clap::ValueHint::Url)]
+    github_api_url: Option<Url>,
+
+    /// GitHub GraphQL API 的 URL，默认根据 REST API 的基础 URL 推断
+    #[arg(long, global = true, env = "KOMAC_GITHUB_GRAPHQL_URL", value_hint = clap::ValueHint::Url)]
+    github_graphql_url: Option<Url>,
+}
+
+impl GitHubApiArgs {
+    /// Makes the configured GitHub instance the one used for the rest of the process.
+    pub fn install(self) -> Result<()> {
+        let api = match self.github_api_url {
+            Some(rest) => GitHubApi::new(rest, self.github_graphql_url)?,
+            None => GitHubApi::default(),
+        };
+        GITHUB_API.get_or_init(|| api);
+        Ok(())
+    }
+}
+
+/// The endpoints of a GitHub instance, which is github.com unless a GitHub Enterprise Server or
+/// mock server is configured.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GitHubApi {
+    rest: Url,
+    graphql: Url,
+    host: String,
+}
+
+impl GitHubApi {
+    /// Creates the endpoints from the base URL of the REST API.
+    ///
+    /// Unless given, the GraphQL endpoint is inferred from the REST API: GitHub Enterprise Server
+    /// serves REST at `/api/v3` and GraphQL at `/api/graphql`, whereas github.com and most mock
+    /// servers serve GraphQL at `/graphql` under the REST base.
+    pub fn new(mut rest: Url, graphql: Option<Url>) -> Result<Self> {
+        if !rest.path().ends_with('/') {
+            rest.set_path(&format!("{}/", rest.path()));
+        }
+        let graphql = match graphql {
+            Some(graphql) => graphql,
+            None if rest.path().ends_with("/api/v3/") => rest.join("../graphql")?,
+            None => rest.join("graphql")?,
+        };
+        let api_host = rest.host_str().ok_or_else(|| eyre!("{rest} 没有主机名"))?;
+        let host = api_host.strip_prefix("api.").unwrap_or(api_host).to_owned();
+        Ok(Self {
+            rest,
+            graphql,
+            host,
+        })
+    }
+
+    pub const fn graphql_url(&self) -> &Url {
+        &self.graphql
+    }
+
+    /// Returns the URL of a REST API endpoint, given its path without a leading slash.
+    pub fn rest_url(&self, path: &str) -> Result<Url, url::ParseError> {
+        self.rest.join(path)
+    }
+
+    /// Returns the host of the web interface, such as `github.com`.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for GitHubApi {
+    fn default() -> Self {
+        Self::new(Url::parse(GITHUB_API_URL).unwrap(), None).unwrap()
+    }
+}
+
+/// Returns the configured GitHub instance, which is github.com unless another was configured.
+pub fn github_api() -> &'static GitHubApi {
+    GITHUB_API.get_or_init(GitHubApi::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use url::Url;
+
+    use crate::github::api::GitHubApi;
+
+    #[rstest]
+    #[case(
+        "https://api.github.com",
+        "https://api.github.com/graphql",
+        "https://api.github.com/repos/owner/repo",
+        "github.com"
+    )]
+    #[case(
+        "https://github.example.com/api/v3",
+        "https://github.example.com/api/graphql",
+        "https://github.example.com/api/v3/repos/owner/repo",
+        "github.example.com"
+    )]
+    #[case(
+        "http://127.0.0.1:8080/",
+        "http://127.0.0.1:8080/graphql",
+        "http://127.0.0.1:8080/repos/owner/repo",
+        "127.0.0.1"
+    )]
+    fn endpoints(
+        #[case] rest: &str,
+        #[case] graphql: &str,
+        #[case] repository: &str,
+        #[case] host: &str,
+    ) {
+        let api = GitHubApi::new(Url::parse(rest).unwrap(), None).unwrap();
+        assert_eq!(api.graphql_url().as_str(), graphql);
+        assert_eq!(
+            api.rest_url("repos/owner/repo").unwrap().as_str(),
+            repository
+        );
+        assert_eq!(api.host(), host);
+    }
+
+    #[test]
+    fn explicit_graphql_url() {
+        let graphql = Url::parse("https://graphql.example.com/").unwrap();
+        let api = GitHubApi::new(
+            Url::parse("https://rest.example.com/").unwrap(),
+            Some(graphql.clone()),
+        )
+        .unwrap();
+        assert_eq!(*api.graphql_url(), graphql);
+    }
+}
@@ -1,8 +1,10 @@
-use std::{collections::BTreeSet, env, future::Future, num::NonZeroU32, ops::Not, str::FromStr};
+use std::{
+    collections::BTreeSet, env, future::Future, mem, num::NonZeroU32, ops::Not, str::FromStr,
+};
 
 use base64ct::{Base64, Encoding};
 use bon::bon;
-use const_format::{formatcp, str_repeat};
+use const_format::str_repeat;
 use cynic::{
     GraphQlError, GraphQlResponse, Id, MutationBuilder, QueryBuilder,
     http::{CynicReqwestError, ReqwestExt},
@@ -30,9 +32,11 @@ use winget_types::{
 
 use crate::{
     commands::{cleanup::MergeState, utils::SPINNER_TICK_RATE},
-    credential::get_default_headers,
+    forge::is_license_file,
     github::{
+        api::{GitHubApi, github_api},
         graphql::{
+            add_comment::{AddComment, AddCommentVariables},
             create_commit::{
                 CommitMessage, CommittableBranch, CreateCommit, CreateCommitOnBranchInput,
                 CreateCommitVariables, FileAddition, FileChanges, FileDeletion,
@@ -41,7 +45,9 @@ use crate::{
                 CreatePullRequest, CreatePullRequestInput, CreatePullRequestVariables,
             },
             create_ref::{CreateRef, CreateRefVariables, Ref as CreateBranchRef},
-            get_all_values::{GetAllValues, GetAllValuesGitObject, GetAllValuesVariables, Tree},
+            get_all_values::{
+                GetAllValues, GetAllValuesGitObject, GetAllValuesVariables, ReleaseAsset, Tree,
+            },
             get_branches::{
                 GetBranches, GetBranchesVariables, PullRequest, PullRequestState, RefConnection,
             },
@@ -51,38 +57,47 @@ use crate::{
             get_existing_pull_request,
             get_existing_pull_request::{GetExistingPullRequest, GetExistingPullRequestVariables},
             get_file_content::GetFileContent,
-            get_repository_info::{GetRepositoryInfo, RepositoryVariables, TargetGitObject},
+            get_ref::{GetRef, GetRefVariables},
+            get_repository_info::{
+                Commit, GetRepositoryInfo, Ref, RepositoryVariables, TargetGitObject,
+            },
             merge_upstream::{MergeUpstream, MergeUpstreamVariables},
             types::{Base64String, GitObjectId, GitRefName},
             update_refs::{RefUpdate, UpdateRefs, UpdateRefsVariables},
         },
         rest::{GITHUB_JSON_MIME, get_tree::GitTree},
+        upstream::upstream,
         utils::{
-            get_branch_name, get_commit_title, get_package_path, is_manifest_file,
-            pull_request_body,
+            amend_comment_body, get_branch_name, get_commit_title, get_package_path,
+            is_manifest_file, pull_request_body,
         },
     },
     manifests::Manifests,
+    network,
     traits::FromHtml,
     update_state::UpdateState,
 };
 
 pub const MICROSOFT: &str = "microsoft";
 pub const WINGET_PKGS: &str = "winget-pkgs";
-pub const WINGET_PKGS_FULL_NAME: &str = formatcp!("{MICROSOFT}/{WINGET_PKGS}");
 pub const GITHUB_HOST: &str = "github.com";
-const GITHUB_GRAPHQL_URL: &str = "https://api.github.com/graphql";
 
 #[derive(Debug, Error)]
 pub enum GitHubError {
     #[error("{}", .0.clone().unwrap_or_default().into_iter().next().map_or_else(|| String::from("Unknown GraphQL error"), |err| err.message))]
     GraphQL(Option<Vec<GraphQlError>>),
-    #[error("{0} 在 {WINGET_PKGS_FULL_NAME} 中不存在")]
+    #[error("{0} 在 {upstream} 中不存在", upstream = upstream())]
     PackageNonExistent(PackageIdentifier),
+    #[error("分支 {branch} 在 {repository} 中不存在")]
+    BranchNonExistent { branch: String, repository: String },
     #[error("在 {path} 中没有找到 {type} 清单")]
     ManifestNotFound { r#type: ManifestType, path: String },
     #[error("在 {path} 中没有找到有效的文件")]
     NoValidFiles { path: String },
+    #[error("拉取请求 {0} 的分支已被删除")]
+    PullRequestBranchDeleted(Url),
+    #[error("拉取请求 {0} 不是从你的分叉创建的")]
+    PullRequestNotOwned(Url),
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
@@ -93,16 +108,23 @@ pub enum GitHubError {
     UrlParseError(#[from] url::ParseError),
 }
 
-pub struct GitHub(Client);
+pub struct GitHub(Client, GitHubApi);
 
 #[bon]
 impl GitHub {
     pub fn new(token: &str) -> Result<Self, GitHubError> {
-        Ok(Self(
-            Client::builder()
-                .default_headers(get_default_headers(Some(token)))
-                .build()?,
-        ))
+        Self::with_api(token, github_api().clone())
+    }
+
+    /// Creates a client for a specific GitHub instance, such as a GitHub Enterprise Server or a
+    /// mock server.
+    pub fn with_api(token: &str, api: GitHubApi) -> Result<Self, GitHubError> {
+        Ok(Self(network::client(Some(token))?, api))
+    }
+
+    /// Returns the host of the GitHub instance's web interface, such as `github.com`.
+    pub fn host(&self) -> &str {
+        self.1.host()
     }
 
     pub async fn get_username(&self) -> Result<String, GitHubError> {
@@ -113,7 +135,7 @@ impl GitHub {
         } else {
             let GraphQlResponse { data, errors } = self
                 .0
-                .post(GITHUB_GRAPHQL_URL)
+                .post(self.1.graphql_url().as_str())
                 .run_graphql(GetCurrentUserLogin::build(()))
                 .await?;
             data.map(|data| data.viewer.login)
@@ -125,9 +147,10 @@ impl GitHub {
         &self,
         package_identifier: &PackageIdentifier,
     ) -> Result<BTreeSet<PackageVersion>, GitHubError> {
+        let upstream = upstream();
         self.get_all_versions(
-            MICROSOFT,
-            WINGET_PKGS,
+            &upstream.owner,
+            &upstream.name,
             &get_package_path(package_identifier, None, None),
         )
         .await
@@ -143,9 +166,10 @@ impl GitHub {
         const TREE: &str = "tree";
         const SEPARATOR: char = '/';
 
-        let endpoint = format!(
-            "https://api.github.com/repos/{owner}/{repo}/git/trees/HEAD:{path}?recursive=true"
-        );
+        let endpoint = self.1.rest_url(&format!(
+            "repos/{owner}/{repo}/git/trees/{}:{path}?recursive=true",
+            upstream().revision()
+        ))?;
 
         let GitTree { tree, .. } = self
             .0
@@ -185,63 +209,14 @@ impl GitHub {
         identifier: &PackageIdentifier,
         latest_version: &PackageVersion,
     ) -> Result<Manifests, GitHubError> {
+        let upstream = upstream();
         let full_package_path = get_package_path(identifier, Some(latest_version), None);
         let content = self
-            .get_directory_content_with_text(MICROSOFT, WINGET_PKGS, &full_package_path)
+            .get_directory_content_with_text(&upstream.owner, &upstream.name, &full_package_path)
             .await?
             .collect::<Vec<_>>();
 
-        let version_manifest = content
-            .iter()
-            .find(|file| is_manifest_file::<VersionManifest>(&file.name, identifier, None))
-            .map(|file| serde_yaml::from_str::<VersionManifest>(&file.text))
-            .ok_or_else(|| GitHubError::ManifestNotFound {
-                r#type: ManifestType::Version,
-                path: full_package_path.clone(),
-            })??;
-
-        let locale_manifests = content
-            .iter()
-            .filter(|file| {
-                is_manifest_file::<LocaleManifest>(
-                    &file.name,
-                    identifier,
-                    Some(&version_manifest.default_locale),
-                )
-            })
-            .map(|file| serde_yaml::from_str::<LocaleManifest>(&file.text))
-            .collect::<serde_yaml::Result<_>>()?;
-
-        let default_locale_manifest = content
-            .iter()
-            .find(|file| {
-                is_manifest_file::<DefaultLocaleManifest>(
-                    &file.name,
-                    identifier,
-                    Some(&version_manifest.default_locale),
-                )
-            })
-            .map(|file| serde_yaml::from_str::<DefaultLocaleManifest>(&file.text))
-            .ok_or_else(|| GitHubError::ManifestNotFound {
-                r#type: ManifestType::DefaultLocale,
-                path: full_package_path.clone(),
-            })??;
-
-        let installer_manifest = content
-            .into_iter()
-            .find(|file| is_manifest_file::<InstallerManifest>(&file.name, identifier, None))
-            .map(|file| serde_yaml::from_str::<InstallerManifest>(&file.text))
-            .ok_or_else(|| GitHubError::ManifestNotFound {
-                r#type: ManifestType::Installer,
-                path: full_package_path.clone(),
-            })??;
-
-        Ok(Manifests {
-            installer: installer_manifest,
-            default_locale: default_locale_manifest,
-            locales: locale_manifests,
-            version: version_manifest,
-        })
+        parse_manifests(content, identifier, &full_package_path)
     }
 
     async fn get_directory_content_with_text(
@@ -252,12 +227,12 @@ impl GitHub {
     ) -> Result<impl Iterator<Item = GitHubFile>, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(GetDirectoryContentWithText::build(
                 GetDirectoryContentVariables {
                     owner,
                     name: repo,
-                    expression: &format!("HEAD:{path}"),
+                    expression: &format!("{}:{path}", upstream().revision()),
                 },
             ))
             .await?;
@@ -281,7 +256,10 @@ impl GitHub {
         manifest_type: ManifestTypeWithLocale,
     ) -> Result<T, GitHubError> {
         let path = get_package_path(identifier, Some(version), Some(&manifest_type));
-        let content = self.get_file_content(MICROSOFT, WINGET_PKGS, &path).await?;
+        let upstream = upstream();
+        let content = self
+            .get_file_content(&upstream.owner, &upstream.name, &path)
+            .await?;
         let manifest = serde_yaml::from_str::<T>(&content)?;
         Ok(manifest)
     }
@@ -294,24 +272,75 @@ impl GitHub {
     ) -> Result<String, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(GetFileContent::build(GetDirectoryContentVariables {
                 owner,
                 name: repo,
-                expression: &format!("HEAD:{path}"),
+                expression: &format!("{}:{path}", upstream().revision()),
             }))
             .await?;
         data.and_then(|data| data.repository?.object?.into_blob_text())
             .ok_or(GitHubError::GraphQL(errors))
     }
 
+    /// Gets the upstream repository, or the fork of it owned by `owner`.
+    ///
+    /// If `branch` is given, the default branch fields describe that branch instead. For the
+    /// upstream repository this defaults to the configured upstream branch so that it is used as
+    /// the base of pull requests, while forks keep their own default branch.
     #[builder(finish_fn = send)]
     pub async fn get_winget_pkgs(
         &self,
         owner: Option<&str>,
+        branch: Option<&str>,
     ) -> Result<RepositoryData, GitHubError> {
-        self.get_repository_info(owner.unwrap_or(MICROSOFT), WINGET_PKGS)
-            .await
+        let upstream = upstream();
+        let branch = match owner {
+            Some(_) => branch,
+            None => branch.or(upstream.branch.as_deref()),
+        };
+        let owner = owner.unwrap_or(&upstream.owner);
+        let mut repository = self.get_repository_info(owner, &upstream.name).await?;
+        if let Some(branch) = branch {
+            let (branch, commit) = self.get_branch(owner, &upstream.name, branch).await?;
+            repository.default_branch_name = branch.name;
+            repository.default_branch_oid = commit.oid;
+            repository.default_branch_ref_id = branch.id;
+            repository.commit_count = commit.history.total_count;
+        }
+        Ok(repository)
+    }
+
+    async fn get_branch(
+        &self,
+        owner: &str,
+        name: &str,
+        branch: &str,
+    ) -> Result<(Ref, Commit), GitHubError> {
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(self.1.graphql_url().as_str())
+            .run_graphql(GetRef::build(GetRefVariables {
+                owner,
+                name,
+                qualified_name: branch,
+            }))
+            .await?;
+
+        let mut branch_ref = data.and_then(|data| data.repository?.ref_).ok_or_else(|| {
+            GitHubError::BranchNonExistent {
+                branch: branch.to_owned(),
+                repository: format!("{owner}/{name}"),
+            }
+        })?;
+
+        let commit = branch_ref
+            .target
+            .take()
+            .and_then(TargetGitObject::into_commit)
+            .ok_or(GitHubError::GraphQL(errors))?;
+
+        Ok((branch_ref, commit))
     }
 
     async fn get_repository_info(
@@ -321,7 +350,7 @@ impl GitHub {
     ) -> Result<RepositoryData, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(GetRepositoryInfo::build(RepositoryVariables {
                 owner,
                 name,
@@ -360,7 +389,7 @@ impl GitHub {
     ) -> Result<CreateBranchRef, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(CreateRef::build(CreateRefVariables {
                 name: &format!("refs/heads/{branch_name}"),
                 oid,
@@ -382,7 +411,7 @@ impl GitHub {
     ) -> Result<Url, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(CreateCommit::build(CreateCommitVariables {
                 input: CreateCommitOnBranchInput {
                     branch: CommittableBranch { id: branch_id },
@@ -411,10 +440,10 @@ impl GitHub {
     ) -> Result<impl Iterator<Item = String>, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(GetDirectoryContent::build(GetDirectoryContentVariables {
                 expression: &format!("{branch_name}:{path}"),
-                name: WINGET_PKGS,
+                name: &upstream().name,
                 owner,
             }))
             .await?;
@@ -436,10 +465,10 @@ impl GitHub {
         loop {
             let GraphQlResponse { data, errors } = self
                 .0
-                .post(GITHUB_GRAPHQL_URL)
+                .post(self.1.graphql_url().as_str())
                 .run_graphql(GetBranches::build(GetBranchesVariables {
                     owner: user,
-                    name: WINGET_PKGS,
+                    name: &upstream().name,
                     cursor: cursor.as_deref(),
                 }))
                 .await?;
@@ -478,7 +507,7 @@ impl GitHub {
                         _ => pull_request.state != PullRequestState::Open,
                     })
                     .find(|pull_request| {
-                        pull_request.repository.name_with_owner == WINGET_PKGS_FULL_NAME
+                        pull_request.repository.name_with_owner == upstream().to_string()
                     })
                 {
                     pr_branch_map.insert(pull_request, branch.name);
@@ -516,7 +545,7 @@ impl GitHub {
         });
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(operation)
             .await?;
         data.and_then(|data| data.create_pull_request?.pull_request)
@@ -533,7 +562,7 @@ impl GitHub {
 
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(UpdateRefs::build(UpdateRefsVariables {
                 ref_updates: branch_names
                     .iter()
@@ -559,39 +588,39 @@ impl GitHub {
         identifier: &PackageIdentifier,
         version: &PackageVersion,
     ) -> Result<Option<get_existing_pull_request::PullRequest>, GitHubError> {
-        self
-            .0
-            .post(GITHUB_GRAPHQL_URL)
-            .run_graphql(GetExistingPullRequest::build(GetExistingPullRequestVariables {
-                query: &format!("repo:{WINGET_PKGS_FULL_NAME} is:pull-request in:title {identifier} {version}"),
-            }))
+        self.0
+            .post(self.1.graphql_url().as_str())
+            .run_graphql(GetExistingPullRequest::build(
+                GetExistingPullRequestVariables {
+                    query: &format!(
+                        "repo:{} is:pull-request in:title {identifier} {version}",
+                        upstream()
+                    ),
+                },
+            ))
             .await
-            .map(|response| response.data.and_then(|data| {
-                data.search
-                    .edges
-                    .into_iter()
-                    .next()?
-                    .node?
-                    .into_pull_request()
-            }))
+            .map(|response| {
+                response.data.and_then(|data| {
+                    let mut pull_requests = data
+                        .search
+                        .edges
+                        .into_iter()
+                        .filter_map(|edge| edge.node?.into_pull_request());
+                    // Prefer an open pull request over closed ones for the same version
+                    let first = pull_requests.next()?;
+                    if first.state == PullRequestState::Open {
+                        return Some(first);
+                    }
+                    Some(
+                        pull_requests
+                            .find(|pull_request| pull_request.state == PullRequestState::Open)
+                            .unwrap_or(first),
+                    )
+                })
+            })
             .map_err(GitHubError::CynicRequest)
     }
 
-    pub fn get_all_values_from_url(
-        &self,
-        url: &DecodedUrl,
-    ) -> Option<impl Future<Output = Result<GitHubValues, GitHubError>> + use<'_>> {
-        let mut parts = url.path_segments()?;
-        let _file_name = parts.next_back()?;
-        let builder = self
-            .get_all_values()
-            .owner(parts.next()?.to_owned())
-            .repo(parts.next()?.to_owned());
-        let _releases = parts.next()?;
-        let _download = parts.next()?;
-        Some(builder.tag_name(parts.join("/")).send())
-    }
-
     #[builder(finish_fn = send)]
     pub async fn get_all_values(
         &self,
@@ -601,7 +630,7 @@ impl GitHub {
     ) -> Result<GitHubValues, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(GetAllValues::build(GetAllValuesVariables {
                 name: &repo,
                 owner: &owner,
@@ -621,21 +650,21 @@ impl GitHub {
             GetAllValuesGitObject::Tree(Tree { entries }) => entries
                 .into_iter()
                 .filter_map(|entry| (entry.type_ == "blob").then_some(entry.name))
-                .find(|name| {
-                    name.rfind('.').map_or_else(
-                        || name.to_ascii_lowercase(),
-                        |dot_index| name[..dot_index].to_ascii_lowercase(),
-                    ) == "license"
-                })
+                .find(|name| is_license_file(name))
                 .and_then(|name| {
-                    format!("https://github.com/{owner}/{repo}/blob/HEAD/{name}")
+                    format!("https://{}/{owner}/{repo}/blob/HEAD/{name}", self.host())
                         .parse::<LicenseUrl>()
                         .ok()
                 }),
             GetAllValuesGitObject::Unknown => None,
         };
 
-        let release = repository.release;
+        let mut release = repository.release;
+
+        let release_assets = release
+            .as_mut()
+            .map(|release| mem::take(&mut release.release_assets.nodes))
+            .unwrap_or_default();
 
         let topics = repository
             .topics
@@ -645,7 +674,7 @@ impl GitHub {
             .collect::<BTreeSet<_>>();
 
         let publisher_support_url = if repository.has_issues_enabled {
-            format!("https://github.com/{owner}/{repo}/issues")
+            format!("https://{}/{owner}/{repo}/issues", self.host())
                 .parse::<PublisherSupportUrl>()
                 .ok()
         } else {
@@ -674,6 +703,7 @@ impl GitHub {
             release_notes_url: release
                 .and_then(|release| release.url.as_str().parse::<ReleaseNotesUrl>().ok()),
             topics: Option::from(topics).filter(|topics| !topics.is_empty()),
+            release_assets,
         })
     }
 
@@ -685,7 +715,7 @@ impl GitHub {
     ) -> Result<(), GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GITHUB_GRAPHQL_URL)
+            .post(self.1.graphql_url().as_str())
             .run_graphql(MergeUpstream::build(MergeUpstreamVariables {
                 branch_ref_id,
                 upstream_target_oid,
@@ -834,6 +864,173 @@ impl GitHub {
         )
         .await
     }
+
+    /// Returns whether a pull request is open and was created from the current user's fork, so
+    /// that new commits can be pushed to its branch.
+    pub async fn can_amend(
+        &self,
+        pull_request: &get_existing_pull_request::PullRequest,
+    ) -> Result<bool, GitHubError> {
+        let Some(head_owner) = pull_request
+            .head_repository_owner
+            .as_ref()
+            .filter(|_| pull_request.state == PullRequestState::Open)
+        else {
+            return Ok(false);
+        };
+        Ok(head_owner.login == self.get_username().await?)
+    }
+
+    /// Replaces the manifests in an open pull request by committing onto its branch, and comments
+    /// on it to describe what changed.
+    #[builder(finish_fn = send)]
+    pub async fn amend_pull_request(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+        versions: Option<&BTreeSet<PackageVersion>>,
+        pull_request: get_existing_pull_request::PullRequest,
+        changes: Vec<(String, String)>,
+    ) -> Result<Url, GitHubError> {
+        let get_existing_pull_request::PullRequest {
+            id,
+            url,
+            head_ref,
+            head_repository_owner,
+            ..
+        } = pull_request;
+        let (Some(head_ref), Some(head_owner)) = (head_ref, head_repository_owner) else {
+            return Err(GitHubError::PullRequestBranchDeleted(url));
+        };
+        if head_owner.login != self.get_username().await? {
+            return Err(GitHubError::PullRequestNotOwned(url));
+        }
+        let head_sha = head_ref
+            .target
+            .and_then(TargetGitObject::into_commit)
+            .map(|commit| commit.oid)
+            .ok_or_else(|| GitHubError::PullRequestBranchDeleted(url.clone()))?;
+
+        // Manifests that are no longer generated, such as a removed locale, are deleted
+        let deleted_paths = self
+            .get_directory_content(
+                &head_owner.login,
+                &head_ref.name,
+                &get_package_path(identifier, Some(version), None),
+            )
+            .await?
+            .filter(|path| !changes.iter().any(|(changed_path, _)| changed_path == path))
+            .collect::<Vec<_>>();
+
+        let commit_title =
+            get_commit_title(identifier, version, &UpdateState::get(version, versions));
+        let additions = changes
+            .iter()
+            .map(|(path, content)| {
+                FileAddition::new(
+                    Base64String::new(Base64::encode_string(content.as_bytes())),
+                    path,
+                )
+            })
+            .collect::<Vec<_>>();
+        let deletions = (!deleted_paths.is_empty()).then(|| {
+            deleted_paths
+                .iter()
+                .map(|path| FileDeletion::new(path.as_str()))
+                .collect::<Vec<_>>()
+        });
+        let commit_url = self
+            .create_commit()
+            .branch_id(&head_ref.id)
+            .head_sha(head_sha)
+            .message(&commit_title)
+            .additions(additions)
+            .maybe_deletions(deletions)
+            .send()
+            .await?;
+
+        self.add_comment(
+            &id,
+            &amend_comment_body(
+                &commit_url,
+                changes.iter().map(|(path, _)| path.as_str()),
+                &deleted_paths,
+            ),
+        )
+        .await?;
+
+        Ok(url)
+    }
+
+    pub async fn add_comment(&self, subject_id: &Id, body: &str) -> Result<Url, GitHubError> {
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(self.1.graphql_url().as_str())
+            .run_graphql(AddComment::build(AddCommentVariables { subject_id, body }))
+            .await?;
+        data.and_then(|data| data.add_comment?.comment_edge?.node)
+            .map(|comment| comment.url)
+            .ok_or(GitHubError::GraphQL(errors))
+    }
+}
+
+/// Parses the manifests of a package version from the files in its directory.
+pub fn parse_manifests(
+    content: Vec<GitHubFile>,
+    identifier: &PackageIdentifier,
+    full_package_path: &str,
+) -> Result<Manifests, GitHubError> {
+    let version_manifest = content
+        .iter()
+        .find(|file| is_manifest_file::<VersionManifest>(&file.name, identifier, None))
+        .map(|file| serde_yaml::from_str::<VersionManifest>(&file.text))
+        .ok_or_else(|| GitHubError::ManifestNotFound {
+            r#type: ManifestType::Version,
+            path: full_package_path.to_owned(),
+        })??;
+
+    let locale_manifests = content
+        .iter()
+        .filter(|file| {
+            is_manifest_file::<LocaleManifest>(
+                &file.name,
+                identifier,
+                Some(&version_manifest.default_locale),
+            )
+        })
+        .map(|file| serde_yaml::from_str::<LocaleManifest>(&file.text))
+        .collect::<serde_yaml::Result<_>>()?;
+
+    let default_locale_manifest = content
+        .iter()
+        .find(|file| {
+            is_manifest_file::<DefaultLocaleManifest>(
+                &file.name,
+                identifier,
+                Some(&version_manifest.default_locale),
+            )
+        })
+        .map(|file| serde_yaml::from_str::<DefaultLocaleManifest>(&file.text))
+        .ok_or_else(|| GitHubError::ManifestNotFound {
+            r#type: ManifestType::DefaultLocale,
+            path: full_package_path.to_owned(),
+        })??;
+
+    let installer_manifest = content
+        .into_iter()
+        .find(|file| is_manifest_file::<InstallerManifest>(&file.name, identifier, None))
+        .map(|file| serde_yaml::from_str::<InstallerManifest>(&file.text))
+        .ok_or_else(|| GitHubError::ManifestNotFound {
+            r#type: ManifestType::Installer,
+            path: full_package_path.to_owned(),
+        })??;
+
+    Ok(Manifests {
+        installer: installer_manifest,
+        default_locale: default_locale_manifest,
+        locales: locale_manifests,
+        version: version_manifest,
+    })
 }
 
 pub struct GitHubValues {
@@ -845,6 +1042,7 @@ pub struct GitHubValues {
     pub release_notes: Option<ReleaseNotes>,
     pub release_notes_url: Option<ReleaseNotesUrl>,
     pub topics: Option<BTreeSet<Tag>>,
+    pub release_assets: Vec<ReleaseAsset>,
 }
 
 pub struct GitHubFile {
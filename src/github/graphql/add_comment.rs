@@ -0,0 +1,66 @@
+use url::Url;
+
+use crate::github::graphql::github_schema::github_schema as schema;
+
+/// <https://docs.github.com/graphql/reference/input-objects#addcommentinput>
+#[derive(cynic::QueryVariables)]
+pub struct AddCommentVariables<'a> {
+    pub subject_id: &'a cynic::Id,
+    pub body: &'a str,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Mutation", variables = "AddCommentVariables")]
+pub struct AddComment {
+    #[arguments(input: { subjectId: $subject_id, body: $body })]
+    pub add_comment: Option<AddCommentPayload>,
+}
+
+/// <https://docs.github.com/graphql/reference/mutations#addcomment>
+#[derive(cynic::QueryFragment)]
+pub struct AddCommentPayload {
+    pub comment_edge: Option<IssueCommentEdge>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#issuecommentedge>
+#[derive(cynic::QueryFragment)]
+pub struct IssueCommentEdge {
+    pub node: Option<IssueComment>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#issuecomment>
+#[derive(cynic::QueryFragment)]
+pub struct IssueComment {
+    pub url: Url,
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::{Id, MutationBuilder};
+    use indoc::indoc;
+
+    use crate::github::graphql::add_comment::{AddComment, AddCommentVariables};
+
+    #[test]
+    fn add_comment_output() {
+        const ADD_COMMENT_MUTATION: &str = indoc! {"
+            mutation AddComment($subjectId: ID!, $body: String!) {
+              addComment(input: {subjectId: $subjectId, body: $body}) {
+                commentEdge {
+                  node {
+                    url
+                  }
+                }
+              }
+            }
+        "};
+
+        let id = Id::new("");
+        let operation = AddComment::build(AddCommentVariables {
+            subject_id: &id,
+            body: "",
+        });
+
+        assert_eq!(operation.query, ADD_COMMENT_MUTATION);
+    }
+}
@@ -70,9 +70,25 @@ pub struct Topic {
 pub struct Release {
     #[cynic(rename = "descriptionHTML")]
     pub description_html: Option<Html>,
+    #[arguments(first: 100)]
+    pub release_assets: ReleaseAssetConnection,
     pub url: Url,
 }
 
+/// <https://docs.github.com/graphql/reference/objects#releaseassetconnection>
+#[derive(cynic::QueryFragment)]
+pub struct ReleaseAssetConnection {
+    #[cynic(flatten)]
+    pub nodes: Vec<ReleaseAsset>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#releaseasset>
+#[derive(cynic::QueryFragment)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub download_url: Url,
+}
+
 /// <https://docs.github.com/graphql/reference/interfaces#repositoryowner>
 #[derive(cynic::QueryFragment)]
 pub struct RepositoryOwner {
@@ -131,6 +147,12 @@ mod tests {
                 }
                 release(tagName: $tagName) {
                   descriptionHTML
+                  releaseAssets(first: 100) {
+                    nodes {
+                      name
+                      downloadUrl
+                    }
+                  }
                   url
                 }
                 repositoryTopics(first: 16) {
@@ -2,7 +2,8 @@ use chrono::{DateTime, Utc};
 use url::Url;
 
 use crate::github::graphql::{
-    get_branches::PullRequestState, github_schema::github_schema as schema,
+    get_branches::PullRequestState, get_repository_info::Ref,
+    github_schema::github_schema as schema,
 };
 
 #[derive(cynic::QueryVariables)]
@@ -10,10 +11,12 @@ pub struct GetExistingPullRequestVariables<'a> {
     pub query: &'a str,
 }
 
+/// Several pull requests can match a version, such as closed ones alongside an open one, so more
+/// than one is fetched for the open one to be found.
 #[derive(cynic::QueryFragment)]
 #[cynic(graphql_type = "Query", variables = "GetExistingPullRequestVariables")]
 pub struct GetExistingPullRequest {
-    #[arguments(first: 1, type: ISSUE, query: $query)]
+    #[arguments(first: 10, type: ISSUE, query: $query)]
     pub search: SearchResultItemConnection,
 }
 
@@ -30,9 +33,18 @@ pub struct SearchResultItemEdge {
 
 #[derive(cynic::QueryFragment)]
 pub struct PullRequest {
+    pub id: cynic::Id,
     pub url: Url,
     pub state: PullRequestState,
     pub created_at: DateTime<Utc>,
+    pub head_ref: Option<Ref>,
+    pub head_repository_owner: Option<RepositoryOwner>,
+}
+
+/// <https://docs.github.com/graphql/reference/interfaces#repositoryowner>
+#[derive(cynic::QueryFragment)]
+pub struct RepositoryOwner {
+    pub login: String,
 }
 
 #[derive(cynic::InlineFragments)]
@@ -64,14 +76,31 @@ mod tests {
     fn get_existing_pull_request_output() {
         const GET_EXISTING_PULL_REQUEST_QUERY: &str = indoc! {r#"
             query GetExistingPullRequest($query: String!) {
-              search(first: 1, type: ISSUE, query: $query) {
+              search(first: 10, type: ISSUE, query: $query) {
                 edges {
                   node {
                     __typename
                     ... on PullRequest {
+                      id
                       url
                       state
                       createdAt
+                      headRef {
+                        name
+                        id
+                        target {
+                          __typename
+                          ... on Commit {
+                            oid
+                            history {
+                              totalCount
+                            }
+                          }
+                        }
+                      }
+                      headRepositoryOwner {
+                        login
+                      }
                     }
                   }
                 }
@@ -0,0 +1,66 @@
+use crate::github::graphql::{get_repository_info::Ref, github_schema::github_schema as schema};
+
+#[derive(cynic::QueryVariables)]
+pub struct GetRefVariables<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub qualified_name: &'a str,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Query", variables = "GetRefVariables")]
+pub struct GetRef {
+    #[arguments(owner: $owner, name: $name)]
+    pub repository: Option<Repository>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#repository>
+#[derive(cynic::QueryFragment)]
+#[cynic(variables = "GetRefVariables")]
+pub struct Repository {
+    #[arguments(qualifiedName: $qualified_name)]
+    #[cynic(rename = "ref")]
+    pub ref_: Option<Ref>,
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::QueryBuilder;
+    use indoc::indoc;
+
+    use crate::github::{
+        github_client::{MICROSOFT, WINGET_PKGS},
+        graphql::get_ref::{GetRef, GetRefVariables},
+    };
+
+    #[test]
+    fn get_ref_output() {
+        const GET_REF_QUERY: &str = indoc! {r#"
+            query GetRef($owner: String!, $name: String!, $qualifiedName: String!) {
+              repository(owner: $owner, name: $name) {
+                ref(qualifiedName: $qualifiedName) {
+                  name
+                  id
+                  target {
+                    __typename
+                    ... on Commit {
+                      oid
+                      history {
+                        totalCount
+                      }
+                    }
+                  }
+                }
+              }
+            }
+        "#};
+
+        let operation = GetRef::build(GetRefVariables {
+            owner: MICROSOFT,
+            name: WINGET_PKGS,
+            qualified_name: "master",
+        });
+
+        assert_eq!(operation.query, GET_REF_QUERY);
+    }
+}
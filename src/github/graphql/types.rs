@@ -18,6 +18,6 @@ pub struct GitObjectId(#[new(into)] String);
 pub struct GitRefName(#[new(into)] String);
 
 /// <https://docs.github.com/en/graphql/reference/scalars#html>
-#[derive(cynic::Scalar, Deref)]
+#[derive(cynic::Scalar, Deref, new)]
 #[cynic(graphql_type = "HTML")]
-pub struct Html(String);
+pub struct Html(#[new(into)] String);
@@ -1,4 +1,6 @@
+pub mod api;
 pub mod github_client;
 pub mod graphql;
 mod rest;
+pub mod upstream;
 pub mod utils;
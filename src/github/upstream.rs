@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+
+use clap::Args;
+use derive_more::Display;
+
+use crate::github::github_client::{MICROSOFT, WINGET_PKGS};
+
+static UPSTREAM: OnceLock<Upstream> = OnceLock::new();
+
+/// The repository that manifests are read from and pull requests are created against.
+///
+/// Forks of the upstream repository are expected to have the same name as it.
+#[derive(Args)]
+pub struct UpstreamArgs {
+    /// 读取清单和创建拉取请求的仓库的所有者
+    #[arg(
+        long = "upstream-owner",
+        global = true,
+        env = "KOMAC_UPSTREAM_OWNER",
+        default_value = MICROSOFT
+    )]
+    owner: String,
+
+    /// 读取清单和创建拉取请求的仓库的名称
+    #[arg(
+        long = "upstream-repo",
+        global = true,
+        env = "KOMAC_UPSTREAM_REPO",
+        default_value = WINGET_PKGS
+    )]
+    name: String,
+
+    /// 读取清单和创建拉取请求的分支，默认为仓库的默认分支
+    #[arg(long = "upstream-branch", global = true, env = "KOMAC_UPSTREAM_BRANCH")]
+    branch: Option<String>,
+}
+
+impl UpstreamArgs {
+    /// Makes the configured repository the upstream for the rest of the process.
+    pub fn install(self) {
+        UPSTREAM.get_or_init(|| Upstream {
+            owner: self.owner,
+            name: self.name,
+            branch: self.branch,
+        });
+    }
+}
+
+#[derive(Display)]
+#[display("{owner}/{name}")]
+pub struct Upstream {
+    pub owner: String,
+    pub name: String,
+    pub branch: Option<String>,
+}
+
+impl Upstream {
+    /// Returns the revision manifests are read from, which is the configured branch or otherwise
+    /// the repository's default branch.
+    pub fn revision(&self) -> &str {
+        self.branch.as_deref().unwrap_or("HEAD")
+    }
+}
+
+/// Returns the upstream repository, which is [microsoft/winget-pkgs] unless another was
+/// configured.
+///
+/// [microsoft/winget-pkgs]: https://github.com/microsoft/winget-pkgs
+pub fn upstream() -> &'static Upstream {
+    UPSTREAM.get_or_init(|| Upstream {
+        owner: MICROSOFT.to_owned(),
+        name: WINGET_PKGS.to_owned(),
+        branch: None,
+    })
+}
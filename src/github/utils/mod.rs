@@ -4,6 +4,7 @@ use bon::builder;
 use clap::{crate_name, crate_version};
 use icu_locid::LanguageIdentifier;
 use rand::Rng;
+use url::Url;
 use uuid::Uuid;
 use winget_types::{
     shared::{
@@ -144,6 +145,28 @@ pub fn pull_request_body(
     body
 }
 
+/// Describes a commit that replaced the manifests of an existing pull request, as a comment on it.
+pub fn amend_comment_body<'a>(
+    commit_url: &Url,
+    updated_paths: impl IntoIterator<Item = &'a str>,
+    deleted_paths: &[String],
+) -> String {
+    let mut body = format!(
+        "### 清单已使用 [{}]({}) v{} 更新 :memo:\n\n{commit_url}\n",
+        crate_name!(),
+        env!("CARGO_PKG_REPOSITORY"),
+        crate_version!()
+    );
+    for path in updated_paths {
+        let _ = write!(body, "\n- 更新 `{path}`");
+    }
+    for path in deleted_paths {
+        let _ = write!(body, "\n- 删除 `{path}`");
+    }
+    body.push('\n');
+    body
+}
+
 pub fn get_branch_name(
     package_identifier: &PackageIdentifier,
     package_version: &PackageVersion,
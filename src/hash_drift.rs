@@ -0,0 +1,145 @@
+use derive_more::Display;
+use itertools::Itertools;
+use winget_types::{
+    installer::Installer,
+    shared::{Sha256String, url::DecodedUrl},
+};
+
+/// An installer URL that served different content than it did before, which means it is not
+/// versioned and the manifest's hash will break whenever a new version is released.
+#[derive(Debug, Display, Eq, PartialEq)]
+#[display("{url} 的 SHA-256 从 {previous_sha_256} 变为 {new_sha_256}")]
+pub struct HashDrift {
+    pub url: DecodedUrl,
+    pub previous_sha_256: Sha256String,
+    pub new_sha_256: Sha256String,
+}
+
+const HTTP: &str = "http";
+const HTTPS: &str = "https";
+
+/// Returns whether a URL that was fetched is the same as the published one, allowing for the
+/// published URL having been upgraded from HTTP to HTTPS when it was downloaded.
+pub fn is_same_url(published: &DecodedUrl, fetched: &DecodedUrl) -> bool {
+    if published == fetched {
+        return true;
+    }
+    if published.scheme() != HTTP || fetched.scheme() != HTTPS {
+        return false;
+    }
+    let mut upgraded = published.clone();
+    upgraded.set_scheme(HTTPS).is_ok() && upgraded == *fetched
+}
+
+/// Returns every URL that is used by both the previous and new installers but now has a
+/// different hash.
+///
+/// New installer URLs have already been upgraded to HTTPS where possible, so a previous HTTP URL
+/// still matches its upgraded form.
+pub fn find_hash_drift<'a>(
+    previous_installers: impl IntoIterator<Item = &'a Installer>,
+    new_installers: &[Installer],
+) -> Vec<HashDrift> {
+    previous_installers
+        .into_iter()
+        .filter_map(|previous_installer| {
+            new_installers
+                .iter()
+                .find(|new_installer| {
+                    is_same_url(&previous_installer.url, &new_installer.url)
+                        && new_installer.sha_256 != previous_installer.sha_256
+                })
+                .map(|new_installer| HashDrift {
+                    url: new_installer.url.clone(),
+                    previous_sha_256: previous_installer.sha_256.clone(),
+                    new_sha_256: new_installer.sha_256.clone(),
+                })
+        })
+        .unique_by(|hash_drift| hash_drift.url.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+    use sha2::{Digest, Sha256};
+    use winget_types::{
+        installer::Installer,
+        shared::{Sha256String, url::DecodedUrl},
+    };
+
+    use crate::hash_drift::{find_hash_drift, is_same_url};
+
+    fn installer(url: &str, content: &[u8]) -> Installer {
+        Installer {
+            url: DecodedUrl::from_str(url).unwrap(),
+            sha_256: Sha256String::from_hasher(&Sha256::digest(content)).unwrap(),
+            ..Installer::default()
+        }
+    }
+
+    #[test]
+    fn same_url_different_hash() {
+        let previous = [installer("https://www.example.com/setup.exe", b"1.0.0")];
+        let new = [installer("https://www.example.com/setup.exe", b"1.1.0")];
+
+        let hash_drift = find_hash_drift(&previous, &new);
+        assert_eq!(hash_drift.len(), 1);
+        assert_eq!(hash_drift[0].url, new[0].url);
+        assert_eq!(hash_drift[0].previous_sha_256, previous[0].sha_256);
+        assert_eq!(hash_drift[0].new_sha_256, new[0].sha_256);
+    }
+
+    #[test]
+    fn versioned_urls() {
+        let previous = [installer(
+            "https://www.example.com/1.0.0/setup.exe",
+            b"1.0.0",
+        )];
+        let new = [installer(
+            "https://www.example.com/1.1.0/setup.exe",
+            b"1.1.0",
+        )];
+
+        assert!(find_hash_drift(&previous, &new).is_empty());
+    }
+
+    #[test]
+    fn upgraded_to_https() {
+        let previous = [installer("http://www.example.com/setup.exe", b"1.0.0")];
+        let new = [installer("https://www.example.com/setup.exe", b"1.1.0")];
+
+        let hash_drift = find_hash_drift(&previous, &new);
+        assert_eq!(hash_drift.len(), 1);
+        assert_eq!(hash_drift[0].url, new[0].url);
+    }
+
+    #[test]
+    fn same_url_same_hash() {
+        let previous = [installer("https://www.example.com/setup.exe", b"1.0.0")];
+
+        assert!(find_hash_drift(&previous, &previous).is_empty());
+    }
+
+    #[rstest]
+    #[case("https://example.com/setup.exe", "https://example.com/setup.exe", true)]
+    #[case("http://example.com/setup.exe", "https://example.com/setup.exe", true)]
+    #[case("http://example.com/setup.exe", "http://example.com/setup.exe", true)]
+    #[case("https://example.com/setup.exe", "http://example.com/setup.exe", false)]
+    #[case(
+        "http://www.example.com/latest/setup.exe",
+        "https://www.example.com/1.0.0/setup.exe",
+        false
+    )]
+    fn same_url(#[case] published: &str, #[case] fetched: &str, #[case] expected: bool) {
+        assert_eq!(
+            is_same_url(
+                &DecodedUrl::from_str(published).unwrap(),
+                &DecodedUrl::from_str(fetched).unwrap()
+            ),
+            expected
+        );
+    }
+}
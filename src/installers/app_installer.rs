@@ -0,0 +1,160 @@
+use std::{fmt, str::FromStr};
+
+use color_eyre::eyre::Result;
+use package_family_name::PackageFamilyName;
+use quick_xml::de::from_str;
+use serde::Deserialize;
+use winget_types::{
+    installer::{Architecture, Installer, InstallerType},
+    shared::url::DecodedUrl,
+};
+
+use crate::file_analyser::{APPX, APPX_BUNDLE};
+
+/// An App Installer file that points at the current version of an MSIX package or bundle.
+///
+/// <https://learn.microsoft.com/uwp/schemas/appinstallerschema/element-appinstaller>
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AppInstaller {
+    #[serde(alias = "MainBundle")]
+    pub main_package: MainPackage,
+    pub update_settings: Option<UpdateSettings>,
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/appinstallerschema/element-mainpackage>
+#[derive(Deserialize)]
+pub struct MainPackage {
+    #[serde(rename = "@Name")]
+    pub name: String,
+    #[serde(rename = "@Publisher")]
+    pub publisher: String,
+    #[serde(rename = "@ProcessorArchitecture")]
+    pub processor_architecture: Option<String>,
+    #[serde(rename = "@Uri")]
+    pub uri: DecodedUrl,
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/appinstallerschema/element-update-settings>
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateSettings {
+    pub on_launch: Option<OnLaunch>,
+    pub automatic_background_task: Option<AutomaticBackgroundTask>,
+    pub force_update_from_any_version: Option<bool>,
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/appinstallerschema/element-onlaunch>
+#[derive(Deserialize)]
+pub struct OnLaunch {
+    #[serde(rename = "@HoursBetweenUpdateChecks")]
+    pub hours_between_update_checks: Option<u16>,
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/appinstallerschema/element-automaticbackgroundtask>
+#[derive(Deserialize)]
+pub struct AutomaticBackgroundTask {}
+
+impl AppInstaller {
+    pub fn new(data: &[u8]) -> Result<Self> {
+        const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+        let xml = std::str::from_utf8(data)?.trim_start_matches(BYTE_ORDER_MARK);
+        Ok(from_str::<Self>(xml)?)
+    }
+
+    /// Creates an installer from what the App Installer file declares about its main package,
+    /// without downloading the package itself.
+    pub fn installer(&self) -> Installer {
+        let main_package = &self.main_package;
+        let is_appx = main_package
+            .uri
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(|file_name| file_name.rsplit_once('.'))
+            .is_some_and(|(_, extension)| {
+                extension.eq_ignore_ascii_case(APPX) || extension.eq_ignore_ascii_case(APPX_BUNDLE)
+            });
+
+        Installer {
+            architecture: main_package
+                .processor_architecture
+                .as_deref()
+                .and_then(|architecture| Architecture::from_str(architecture).ok())
+                .unwrap_or(Architecture::Neutral),
+            r#type: if is_appx {
+                Some(InstallerType::Appx)
+            } else {
+                Some(InstallerType::Msix)
+            },
+            url: main_package.uri.clone(),
+            package_family_name: Some(PackageFamilyName::new(
+                &main_package.name,
+                &main_package.publisher,
+            )),
+            ..Installer::default()
+        }
+    }
+}
+
+impl fmt::Display for UpdateSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut settings = Vec::new();
+        if let Some(on_launch) = &self.on_launch {
+            settings.push(on_launch.hours_between_update_checks.map_or_else(
+                || String::from("OnLaunch"),
+                |hours| format!("OnLaunch (HoursBetweenUpdateChecks={hours})"),
+            ));
+        }
+        if self.automatic_background_task.is_some() {
+            settings.push(String::from("AutomaticBackgroundTask"));
+        }
+        if self.force_update_from_any_version == Some(true) {
+            settings.push(String::from("ForceUpdateFromAnyVersion"));
+        }
+        write!(f, "{}", settings.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use winget_types::installer::{Architecture, InstallerType};
+
+    use crate::installers::app_installer::AppInstaller;
+
+    #[test]
+    fn main_bundle() {
+        const APP_INSTALLER: &str = indoc! {r#"
+            <?xml version="1.0" encoding="utf-8"?>
+            <AppInstaller
+                xmlns="http://schemas.microsoft.com/appx/appinstaller/2021"
+                Version="1.2.3.0"
+                Uri="https://example.com/app.appinstaller">
+                <MainBundle
+                    Name="Example.App"
+                    Publisher="CN=Example"
+                    Version="1.2.3.0"
+                    Uri="https://example.com/1.2.3/app.msixbundle" />
+                <UpdateSettings>
+                    <OnLaunch HoursBetweenUpdateChecks="12" />
+                    <AutomaticBackgroundTask />
+                </UpdateSettings>
+            </AppInstaller>
+        "#};
+
+        let app_installer = AppInstaller::new(APP_INSTALLER.as_bytes()).unwrap();
+        let installer = app_installer.installer();
+        assert_eq!(installer.architecture, Architecture::Neutral);
+        assert_eq!(installer.r#type, Some(InstallerType::Msix));
+        assert_eq!(app_installer.main_package.name, "Example.App");
+        assert_eq!(
+            app_installer.main_package.uri.as_str(),
+            "https://example.com/1.2.3/app.msixbundle"
+        );
+        assert_eq!(
+            app_installer.update_settings.unwrap().to_string(),
+            "OnLaunch (HoursBetweenUpdateChecks=12), AutomaticBackgroundTask"
+        );
+    }
+}
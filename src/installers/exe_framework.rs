@@ -0,0 +1,269 @@
+use std::fmt;
+
+use memchr::memmem;
+use winget_types::installer::{
+    Architecture, Installer, InstallerType,
+    switches::{InstallerSwitches, SilentSwitch, SilentWithProgressSwitch},
+};
+use yara_x::mods::PE;
+
+use crate::traits::FromMachine;
+
+/// An installer framework that can be recognised from the PE structure of its setup executable but
+/// whose installation data is not read.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExeFramework {
+    InstallShield,
+    AdvancedInstaller,
+    Wise,
+    SetupFactory,
+    QtInstallerFramework,
+    InstallAware,
+    /// A 7-Zip self-extracting archive, which is only an installer if it runs a program from the
+    /// archive once it has been extracted.
+    SevenZipSfx {
+        runs_program: bool,
+    },
+}
+
+impl ExeFramework {
+    pub fn new(data: &[u8], pe: &PE) -> Option<Self> {
+        let overlay_offset = pe
+            .overlay
+            .offset
+            .and_then(|offset| usize::try_from(offset).ok())
+            .filter(|&offset| offset < data.len());
+
+        let overlay = overlay_offset.map_or(&[][..], |offset| &data[offset..]);
+
+        Self::from_trailer(data)
+            .or_else(|| Self::from_overlay(overlay))
+            .or_else(|| Self::from_sections(pe))
+            .or_else(|| {
+                pe.version_info
+                    .values()
+                    .find_map(|value| Self::from_version_info(value))
+            })
+    }
+
+    /// Qt Installer Framework and Advanced Installer append their data with a marker at the very
+    /// end of the file.
+    fn from_trailer(data: &[u8]) -> Option<Self> {
+        /// <https://code.qt.io/cgit/installer-framework/installer-framework.git/tree/src/libs/installer/binarycontent.h>
+        const QT_IFW_MAGIC_COOKIE: [u8; 8] = 0xC263_0A1C_99D6_68F8_u64.to_le_bytes();
+        const ADVANCED_INSTALLER_FOOTER: &[u8] = b"ADVINSTSFX";
+        const TRAILER_LEN: usize = 1 << 12;
+
+        if data.ends_with(&QT_IFW_MAGIC_COOKIE) {
+            return Some(Self::QtInstallerFramework);
+        }
+
+        let trailer = &data[data.len().saturating_sub(TRAILER_LEN)..];
+        memmem::find(trailer, ADVANCED_INSTALLER_FOOTER).map(|_| Self::AdvancedInstaller)
+    }
+
+    fn from_overlay(overlay: &[u8]) -> Option<Self> {
+        const INSTALLSHIELD: &[u8] = b"InstallShield";
+        const ISSETUPSTREAM: &[u8] = b"ISSetupStream";
+        /// <https://github.com/Puyodead1/sfextract>
+        const SETUP_FACTORY: [u8; 8] = [0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7];
+        const SEVEN_ZIP: [u8; 6] = [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+        const SEVEN_ZIP_SFX_CONFIG: &[u8] = b";!@Install@!UTF-8!";
+        const SEVEN_ZIP_SFX_CONFIG_END: &[u8] = b";!@InstallEnd@!";
+        /// <https://documentation.help/7-Zip/sfx.htm>
+        const SEVEN_ZIP_SFX_RUN_PARAMETERS: [&[u8]; 2] = [b"RunProgram", b"ExecuteFile"];
+
+        if overlay.starts_with(INSTALLSHIELD) || overlay.starts_with(ISSETUPSTREAM) {
+            Some(Self::InstallShield)
+        } else if overlay.starts_with(&SETUP_FACTORY) {
+            Some(Self::SetupFactory)
+        } else if overlay.starts_with(SEVEN_ZIP_SFX_CONFIG) {
+            let config = memmem::find(overlay, SEVEN_ZIP_SFX_CONFIG_END)
+                .map_or(overlay, |end| &overlay[..end]);
+            Some(Self::SevenZipSfx {
+                runs_program: SEVEN_ZIP_SFX_RUN_PARAMETERS
+                    .iter()
+                    .any(|parameter| memmem::find(config, parameter).is_some()),
+            })
+        } else if overlay.starts_with(&SEVEN_ZIP) {
+            // An archive without a config is extracted by the GUI module without running anything
+            Some(Self::SevenZipSfx {
+                runs_program: false,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn from_sections(pe: &PE) -> Option<Self> {
+        const WISE_SECTION: &[u8] = b".WISE";
+
+        pe.sections
+            .iter()
+            .any(|section| section.name() == WISE_SECTION)
+            .then_some(Self::Wise)
+    }
+
+    fn from_version_info(value: &str) -> Option<Self> {
+        const INSTALLSHIELD: &str = "installshield";
+        const INSTALLAWARE: &str = "installaware";
+        const SETUP_FACTORY: &str = "setup factory";
+        const WISE: &str = "wise installation";
+        const ADVANCED_INSTALLER: &str = "advanced installer";
+
+        let value = value.to_ascii_lowercase();
+        [
+            (INSTALLSHIELD, Self::InstallShield),
+            (INSTALLAWARE, Self::InstallAware),
+            (SETUP_FACTORY, Self::SetupFactory),
+            (WISE, Self::Wise),
+            (ADVANCED_INSTALLER, Self::AdvancedInstaller),
+        ]
+        .into_iter()
+        .find_map(|(keyword, framework)| value.contains(keyword).then_some(framework))
+    }
+
+    /// Returns the switches that install silently and silently with progress, or `None` for a
+    /// portable executable, which isn't run with any switches.
+    pub const fn switches(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::InstallShield => Some((r#"/s /v"/qn""#, r#"/s /v"/qb""#)),
+            Self::AdvancedInstaller => {
+                Some(("/exenoui /quiet /norestart", "/exenoui /passive /norestart"))
+            }
+            Self::Wise | Self::InstallAware => Some(("/s", "/s")),
+            Self::SetupFactory => Some(("/S", "/S")),
+            Self::QtInstallerFramework => Some((
+                "install --accept-licenses --default-answer --confirm-command",
+                "install --accept-licenses --default-answer --confirm-command",
+            )),
+            Self::SevenZipSfx { runs_program: true } => Some(("-y", "-y")),
+            Self::SevenZipSfx {
+                runs_program: false,
+            } => None,
+        }
+    }
+
+    /// Returns the installer type of an executable made with the framework.
+    ///
+    /// None of these frameworks are Burn, which is read in full before frameworks are checked.
+    pub const fn installer_type(self) -> InstallerType {
+        match self {
+            Self::InstallShield
+            | Self::AdvancedInstaller
+            | Self::Wise
+            | Self::SetupFactory
+            | Self::QtInstallerFramework
+            | Self::InstallAware
+            | Self::SevenZipSfx { runs_program: true } => InstallerType::Exe,
+            Self::SevenZipSfx {
+                runs_program: false,
+            } => InstallerType::Portable,
+        }
+    }
+
+    pub fn installer(self, pe: &PE) -> Installer {
+        Installer {
+            architecture: Architecture::from_machine(pe.machine()),
+            r#type: Some(self.installer_type()),
+            switches: self
+                .switches()
+                .map(|(silent, silent_with_progress)| InstallerSwitches {
+                    silent: silent.parse::<SilentSwitch>().ok(),
+                    silent_with_progress: silent_with_progress
+                        .parse::<SilentWithProgressSwitch>()
+                        .ok(),
+                    ..InstallerSwitches::default()
+                }),
+            ..Installer::default()
+        }
+    }
+}
+
+impl fmt::Display for ExeFramework {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InstallShield => "InstallShield",
+            Self::AdvancedInstaller => "Advanced Installer",
+            Self::Wise => "Wise",
+            Self::SetupFactory => "Setup Factory",
+            Self::QtInstallerFramework => "Qt Installer Framework",
+            Self::InstallAware => "InstallAware",
+            Self::SevenZipSfx { .. } => "7-Zip SFX",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use winget_types::installer::InstallerType;
+
+    use crate::installers::exe_framework::ExeFramework;
+
+    #[rstest]
+    #[case(b"InstallShield\0\0\0", Some(ExeFramework::InstallShield))]
+    #[case(b"ISSetupStream\0\0\0", Some(ExeFramework::InstallShield))]
+    #[case(&[0xE0, 0xE1, 0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0x00], Some(ExeFramework::SetupFactory))]
+    #[case(
+        &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C, 0x00],
+        Some(ExeFramework::SevenZipSfx { runs_program: false })
+    )]
+    #[case(
+        b";!@Install@!UTF-8!\r\nRunProgram=\"setup.exe\"\r\n;!@InstallEnd@!",
+        Some(ExeFramework::SevenZipSfx { runs_program: true })
+    )]
+    #[case(
+        b";!@Install@!UTF-8!\r\nTitle=\"App\"\r\n;!@InstallEnd@!7z",
+        Some(ExeFramework::SevenZipSfx { runs_program: false })
+    )]
+    #[case(b"PK\x03\x04", None)]
+    #[case(b"", None)]
+    fn overlay(#[case] overlay: &[u8], #[case] expected: Option<ExeFramework>) {
+        assert_eq!(ExeFramework::from_overlay(overlay), expected);
+    }
+
+    #[rstest]
+    #[case("InstallShield (R) Setup Launcher", Some(ExeFramework::InstallShield))]
+    #[case("Created with Setup Factory 9", Some(ExeFramework::SetupFactory))]
+    #[case("Wise Installation Wizard", Some(ExeFramework::Wise))]
+    #[case("Setup", None)]
+    fn version_info(#[case] value: &str, #[case] expected: Option<ExeFramework>) {
+        assert_eq!(ExeFramework::from_version_info(value), expected);
+    }
+
+    #[rstest]
+    #[case(ExeFramework::SevenZipSfx { runs_program: false }, InstallerType::Portable, None)]
+    #[case(
+        ExeFramework::SevenZipSfx { runs_program: true },
+        InstallerType::Exe,
+        Some(("-y", "-y"))
+    )]
+    #[case(ExeFramework::Wise, InstallerType::Exe, Some(("/s", "/s")))]
+    fn installer_type_and_switches(
+        #[case] framework: ExeFramework,
+        #[case] installer_type: InstallerType,
+        #[case] switches: Option<(&str, &str)>,
+    ) {
+        assert_eq!(framework.installer_type(), installer_type);
+        assert_eq!(framework.switches(), switches);
+    }
+
+    #[test]
+    fn trailer() {
+        let mut data = vec![0; 64];
+        data.extend_from_slice(&0xC263_0A1C_99D6_68F8_u64.to_le_bytes());
+        assert_eq!(
+            ExeFramework::from_trailer(&data),
+            Some(ExeFramework::QtInstallerFramework)
+        );
+
+        let mut data = vec![0; 64];
+        data.extend_from_slice(b"ADVINSTSFX");
+        data.extend_from_slice(&[0; 8]);
+        assert_eq!(
+            ExeFramework::from_trailer(&data),
+            Some(ExeFramework::AdvancedInstaller)
+        );
+    }
+}
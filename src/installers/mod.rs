@@ -1,8 +1,11 @@
+pub mod app_installer;
 pub mod burn;
+pub mod exe_framework;
 pub mod inno;
 pub mod msi;
 pub mod msix_family;
 pub mod nsis;
 pub mod possible_installers;
+pub mod squirrel;
 pub mod utils;
 pub mod zip;
@@ -19,6 +19,7 @@ use crate::installers::utils::{
     RELATIVE_APP_DATA, RELATIVE_COMMON_FILES_32, RELATIVE_COMMON_FILES_64, RELATIVE_LOCAL_APP_DATA,
     RELATIVE_PROGRAM_FILES_32, RELATIVE_PROGRAM_FILES_64, RELATIVE_TEMP_FOLDER,
     RELATIVE_WINDOWS_DIR,
+    release_date::{ReleaseDate, ReleaseDateSource},
 };
 
 const PROPERTY: &str = "Property";
@@ -44,6 +45,7 @@ type DirectoryTable = HashMap<String, (Option<String>, String)>;
 
 pub struct Msi {
     pub installer: Installer,
+    pub release_date: Option<ReleaseDate>,
 }
 
 impl Msi {
@@ -96,7 +98,15 @@ impl Msi {
             }
         };
 
+        let release_date = msi
+            .summary_info()
+            .creation_time()
+            .and_then(|creation_time| {
+                ReleaseDate::from_system_time(creation_time, ReleaseDateSource::MsiCreationTime)
+            });
+
         Ok(Self {
+            release_date,
             installer: Installer {
                 locale: property_table.remove(PRODUCT_LANGUAGE).and_then(|code| {
                     Language::from_code(code.parse::<u16>().ok()?)
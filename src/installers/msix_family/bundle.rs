@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeSet,
     io,
     io::{Cursor, Read, Seek},
 };
@@ -9,16 +10,22 @@ use memmap2::Mmap;
 use package_family_name::PackageFamilyName;
 use quick_xml::de::from_str;
 use serde::Deserialize;
-use winget_types::installer::Installer;
+use winget_types::installer::{Dependencies, Installer};
 use zip::ZipArchive;
 
-use crate::installers::msix_family::{
-    Msix,
-    utils::{hash_signature, read_manifest},
+use crate::installers::{
+    msix_family::{
+        Msix,
+        signature::verify_package,
+        utils::{block_map_date, hash_signature, read_manifest},
+    },
+    utils::{release_date::ReleaseDate, signer::Signer},
 };
 
 pub struct MsixBundle {
     pub installers: Vec<Installer>,
+    pub signer: Option<Signer>,
+    pub release_date: Option<ReleaseDate>,
 }
 
 const APPX_BUNDLE_MANIFEST_PATH: &str = "AppxMetadata/AppxBundleManifest.xml";
@@ -38,27 +45,82 @@ impl MsixBundle {
             &bundle_manifest.identity.publisher,
         );
 
+        let signer = verify_package(&mut zip, &bundle_manifest.identity.publisher);
+        let release_date = block_map_date(&mut zip);
+
+        let installers = bundle_manifest
+            .packages
+            .package
+            .into_iter()
+            .filter(|package| package.r#type == PackageType::Application)
+            .map(|package| {
+                let mut embedded_msix = zip.by_name(&package.file_name)?;
+                let mut temp_file = tempfile::tempfile()?;
+                io::copy(&mut embedded_msix, &mut temp_file)?;
+                let map = unsafe { Mmap::map(&temp_file) }?;
+                Msix::new_nested(Cursor::new(map.as_ref()))
+            })
+            .map_ok(|msix| Installer {
+                signature_sha_256: Some(signature_sha_256.clone()),
+                package_family_name: Some(package_family_name.clone()),
+                ..msix.installer
+            })
+            .collect::<Result<Vec<_>>>()?;
+
         Ok(Self {
-            installers: bundle_manifest
-                .packages
-                .package
-                .into_iter()
-                .filter(|package| package.r#type == PackageType::Application)
-                .map(|package| {
-                    let mut embedded_msix = zip.by_name(&package.file_name)?;
-                    let mut temp_file = tempfile::tempfile()?;
-                    io::copy(&mut embedded_msix, &mut temp_file)?;
-                    let map = unsafe { Mmap::map(&temp_file) }?;
-                    Msix::new(Cursor::new(map.as_ref()))
-                })
-                .map_ok(|msix| Installer {
-                    signature_sha_256: Some(signature_sha_256.clone()),
-                    package_family_name: Some(package_family_name.clone()),
-                    ..msix.installer
-                })
-                .collect::<Result<Vec<_>>>()?,
+            installers: Self::merge_architectures(installers),
+            signer,
+            release_date,
         })
     }
+
+    /// Merges packages that target the same architecture, such as those built for different device
+    /// families, into a single installer per architecture.
+    fn merge_architectures(installers: Vec<Installer>) -> Vec<Installer> {
+        fn union<T: Ord>(target: &mut Option<BTreeSet<T>>, source: Option<BTreeSet<T>>) {
+            if let Some(source) = source {
+                target.get_or_insert_default().extend(source);
+            }
+        }
+
+        let mut merged = Vec::<Installer>::with_capacity(installers.len());
+        for installer in installers {
+            let Some(existing) = merged
+                .iter_mut()
+                .find(|existing| existing.architecture == installer.architecture)
+            else {
+                merged.push(installer);
+                continue;
+            };
+
+            union(&mut existing.platform, installer.platform);
+            existing.minimum_os_version = existing
+                .minimum_os_version
+                .take()
+                .into_iter()
+                .chain(installer.minimum_os_version)
+                .min();
+            union(&mut existing.file_extensions, installer.file_extensions);
+            union(&mut existing.capabilities, installer.capabilities);
+            union(
+                &mut existing.restricted_capabilities,
+                installer.restricted_capabilities,
+            );
+            if let Some(package_dependencies) = installer
+                .dependencies
+                .and_then(|dependencies| dependencies.package_dependencies)
+            {
+                union(
+                    &mut existing
+                        .dependencies
+                        .get_or_insert_with(Dependencies::default)
+                        .package_dependencies,
+                    Some(package_dependencies),
+                );
+            }
+        }
+        merged
+    }
 }
 
 /// <https://learn.microsoft.com/uwp/schemas/bundlemanifestschema/element-bundle>
@@ -1,4 +1,6 @@
 pub mod bundle;
+mod package_dependency;
+mod signature;
 mod utils;
 
 use std::{
@@ -12,8 +14,9 @@ use package_family_name::PackageFamilyName;
 use quick_xml::{Reader, events::Event};
 use winget_types::{
     installer::{
-        AppsAndFeaturesEntry, Architecture, FileExtension, InstallationMetadata, Installer,
-        InstallerType, MinimumOSVersion, Platform, UpgradeBehavior,
+        AppsAndFeaturesEntry, Architecture, Dependencies as InstallerDependencies, FileExtension,
+        InstallationMetadata, Installer, InstallerType, MinimumOSVersion, Platform,
+        UpgradeBehavior,
     },
     shared::Version,
 };
@@ -21,20 +24,40 @@ use zip::ZipArchive;
 
 use crate::{
     file_analyser::MSIX,
-    installers::msix_family::utils::{get_install_location, hash_signature, read_manifest},
+    installers::{
+        msix_family::{
+            package_dependency::PackageDependency,
+            signature::{verify_contents, verify_package},
+            utils::{block_map_date, get_install_location, hash_signature, read_manifest},
+        },
+        utils::{release_date::ReleaseDate, signer::Signer},
+    },
 };
 
 pub struct Msix {
     pub installer: Installer,
+    pub signer: Option<Signer>,
+    pub release_date: Option<ReleaseDate>,
 }
 
 const APPX_MANIFEST_XML: &str = "AppxManifest.xml";
 pub const APPX_SIGNATURE_P7X: &str = "AppxSignature.p7x";
+pub const APPX_BLOCK_MAP_XML: &str = "AppxBlockMap.xml";
 
 const MSIX_MIN_VERSION: MinimumOSVersion = MinimumOSVersion::new(10, 0, 17763, 0);
 
 impl Msix {
     pub fn new<R: Read + Seek>(reader: R) -> Result<Self> {
+        Self::read(reader, false)
+    }
+
+    /// Reads a package embedded in a bundle. These are covered by the bundle's signature rather
+    /// than signed by themselves, so only their block map is verified.
+    pub fn new_nested<R: Read + Seek>(reader: R) -> Result<Self> {
+        Self::read(reader, true)
+    }
+
+    fn read<R: Read + Seek>(reader: R, nested: bool) -> Result<Self> {
         let mut zip = ZipArchive::new(reader)?;
 
         let mut appx_manifest = read_manifest(&mut zip, APPX_MANIFEST_XML)?;
@@ -108,6 +131,26 @@ impl Msix {
                                 });
                         }
                     }
+                    b"PackageDependency" => {
+                        let mut package_dependency = PackageDependency::default();
+                        for attribute in event.attributes().flatten() {
+                            match attribute.key.as_ref() {
+                                b"Name" => {
+                                    package_dependency.name =
+                                        String::from_utf8_lossy(&attribute.value).into_owned();
+                                }
+                                b"MinVersion" => {
+                                    package_dependency.min_version =
+                                        String::from_utf8_lossy(&attribute.value).into_owned();
+                                }
+                                _ => continue,
+                            }
+                        }
+                        manifest
+                            .dependencies
+                            .package_dependency
+                            .insert(package_dependency);
+                    }
                     b"FileType" => {
                         if let Ok(extension) = FileExtension::new(
                             reader
@@ -145,6 +188,14 @@ impl Msix {
             }
         }
 
+        let signer = if nested {
+            verify_contents(&mut zip);
+            None
+        } else {
+            verify_package(&mut zip, &manifest.identity.publisher)
+        };
+        let release_date = block_map_date(&mut zip);
+
         let is_appx = manifest
             .dependencies
             .target_device_family
@@ -155,6 +206,13 @@ impl Msix {
                 !appx_manifest.contains(MSIX)
             };
 
+        let package_dependencies = manifest
+            .dependencies
+            .package_dependency
+            .iter()
+            .filter_map(PackageDependency::to_winget)
+            .collect::<BTreeSet<_>>();
+
         Ok(Self {
             installer: Installer {
                 platform: Some(
@@ -189,6 +247,12 @@ impl Msix {
                     .filter(|capabilities| !capabilities.is_empty()),
                 restricted_capabilities: Option::from(manifest.capabilities.restricted)
                     .filter(|restricted| !restricted.is_empty()),
+                dependencies: Option::from(package_dependencies)
+                    .filter(|package_dependencies| !package_dependencies.is_empty())
+                    .map(|package_dependencies| InstallerDependencies {
+                        package_dependencies: Some(package_dependencies),
+                        ..InstallerDependencies::default()
+                    }),
                 apps_and_features_entries: Some(vec![AppsAndFeaturesEntry {
                     display_name: Some(manifest.properties.display_name),
                     publisher: Some(manifest.properties.publisher_display_name),
@@ -207,6 +271,8 @@ impl Msix {
                 }),
                 ..Installer::default()
             },
+            signer,
+            release_date,
         })
     }
 }
@@ -242,6 +308,7 @@ struct Properties {
 #[derive(Default)]
 pub struct Dependencies {
     pub target_device_family: BTreeSet<TargetDeviceFamily>,
+    pub package_dependency: BTreeSet<PackageDependency>,
 }
 
 /// <https://learn.microsoft.com/uwp/schemas/appxpackage/uapmanifestschema/element-targetdevicefamily>
@@ -0,0 +1,111 @@
+use tracing::debug;
+use winget_types::{installer::PackageDependencies, shared::PackageVersion};
+
+/// Framework packages that MSIX packages commonly depend on, mapped to their winget package
+/// identifiers.
+///
+/// The boolean indicates whether the framework's package version can be used as the winget
+/// minimum version. The Windows App Runtime and WinUI frameworks, for example, have MSIX versions
+/// that are unrelated to the versions in winget-pkgs.
+const FRAMEWORKS: [(&str, &str, bool); 3] = [
+    (
+        "Microsoft.VCLibs.140.00.UWPDesktop",
+        "Microsoft.VCLibs.Desktop.14",
+        true,
+    ),
+    (
+        "Microsoft.WindowsAppRuntime.",
+        "Microsoft.WindowsAppRuntime.",
+        false,
+    ),
+    ("Microsoft.UI.Xaml.", "Microsoft.UI.Xaml.", false),
+];
+
+/// <https://learn.microsoft.com/uwp/schemas/appxpackage/uapmanifestschema/element-packagedependency>
+#[derive(Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct PackageDependency {
+    pub name: String,
+    pub min_version: String,
+}
+
+impl PackageDependency {
+    /// Maps the dependency to the winget package that provides it.
+    ///
+    /// Framework names ending in a separator are prefixes, such as `Microsoft.WindowsAppRuntime.1.5`,
+    /// where the rest of the name is carried over to the winget package identifier.
+    pub fn to_winget(&self) -> Option<PackageDependencies> {
+        let dependency = FRAMEWORKS
+            .iter()
+            .find_map(|&(name, identifier, has_version)| {
+                let identifier = if name.ends_with('.') {
+                    format!("{identifier}{}", self.name.strip_prefix(name)?)
+                } else if self.name == name {
+                    identifier.to_owned()
+                } else {
+                    return None;
+                };
+                Some(PackageDependencies {
+                    package_identifier: identifier.parse().ok()?,
+                    minimum_version: has_version
+                        .then(|| self.min_version.parse::<PackageVersion>().ok())
+                        .flatten(),
+                })
+            });
+
+        if dependency.is_none() {
+            debug!(
+                name = self.name,
+                min_version = self.min_version,
+                "No winget package is known for MSIX dependency"
+            );
+        }
+
+        dependency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::installers::msix_family::package_dependency::PackageDependency;
+
+    #[rstest]
+    #[case(
+        "Microsoft.VCLibs.140.00.UWPDesktop",
+        "14.0.33519.0",
+        Some(("Microsoft.VCLibs.Desktop.14", Some("14.0.33519.0")))
+    )]
+    #[case(
+        "Microsoft.WindowsAppRuntime.1.5",
+        "5001.178.1908.0",
+        Some(("Microsoft.WindowsAppRuntime.1.5", None))
+    )]
+    #[case(
+        "Microsoft.UI.Xaml.2.8",
+        "8.2310.30001.0",
+        Some(("Microsoft.UI.Xaml.2.8", None))
+    )]
+    #[case("Microsoft.NET.Native.Runtime.2.2", "2.2.28604.0", None)]
+    fn winget_package_dependency(
+        #[case] name: &str,
+        #[case] min_version: &str,
+        #[case] expected: Option<(&str, Option<&str>)>,
+    ) {
+        let dependency = PackageDependency {
+            name: name.to_owned(),
+            min_version: min_version.to_owned(),
+        }
+        .to_winget();
+        assert_eq!(
+            dependency.as_ref().map(|dependency| (
+                dependency.package_identifier.as_str(),
+                dependency
+                    .minimum_version
+                    .as_ref()
+                    .map(|version| version.as_str())
+            )),
+            expected
+        );
+    }
+}
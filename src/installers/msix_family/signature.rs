@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Seek},
+};
+
+use base64ct::{Base64, Encoding};
+use chrono::{DateTime, Utc};
+use cms::{
+    cert::{
+        CertificateChoices,
+        x509::{Certificate, der::Decode, time::Time},
+    },
+    content_info::ContentInfo,
+    signed_data::{SignedData, SignerIdentifier},
+};
+use color_eyre::eyre::{OptionExt, Result, eyre};
+use percent_encoding::percent_decode_str;
+use quick_xml::de::from_str;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use zip::ZipArchive;
+
+use crate::installers::{
+    msix_family::{APPX_BLOCK_MAP_XML, APPX_SIGNATURE_P7X, utils::read_manifest},
+    utils::signer::Signer,
+};
+
+/// Verifies the signature and block map of an MSIX package or bundle, warning about any problems.
+///
+/// Returns the signer of the package if its signature could be read.
+pub fn verify_package<R: Read + Seek>(zip: &mut ZipArchive<R>, publisher: &str) -> Option<Signer> {
+    let signer = match read_signer(zip) {
+        Ok(signer) => {
+            if !signer.is_subject(publisher) {
+                warn!(
+                    "签名者 \"{}\" 与包标识的发布者 \"{publisher}\" 不一致",
+                    signer.subject
+                );
+            }
+            Some(signer)
+        }
+        Err(error) => {
+            warn!("无法读取 {APPX_SIGNATURE_P7X} 中的签名者: {error}");
+            None
+        }
+    };
+
+    verify_contents(zip);
+
+    signer
+}
+
+/// Verifies that the contents of a package match the hashes in its own block map, warning about
+/// any that do not.
+pub fn verify_contents<R: Read + Seek>(zip: &mut ZipArchive<R>) {
+    match verify_block_map(zip) {
+        Ok(mismatches) if !mismatches.is_empty() => {
+            warn!(
+                "{APPX_BLOCK_MAP_XML} 中的哈希与包内容不匹配: {}",
+                mismatches.join(", ")
+            );
+        }
+        Ok(_) => {}
+        Err(error) => warn!("无法验证 {APPX_BLOCK_MAP_XML}: {error}"),
+    }
+}
+
+/// Reads the signing certificate from the PKCS #7 signature of an MSIX package.
+///
+/// <https://learn.microsoft.com/windows/msix/package/signing-package-overview>
+fn read_signer<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Signer> {
+    /// `AppxSignature.p7x` is a DER encoded PKCS #7 signature prefixed with this magic
+    const PKCX: &[u8; 4] = b"PKCX";
+
+    let mut signature_file = zip.by_name(APPX_SIGNATURE_P7X)?;
+    let mut signature = Vec::with_capacity(usize::try_from(signature_file.size())?);
+    signature_file.read_to_end(&mut signature)?;
+
+    let signature = signature
+        .strip_prefix(PKCX)
+        .ok_or_eyre(format!("{APPX_SIGNATURE_P7X} 缺少 PKCX 标头"))?;
+
+    let signed_data = ContentInfo::from_der(signature)
+        .and_then(|content_info| content_info.content.decode_as::<SignedData>())
+        .map_err(|error| eyre!("{error}"))?;
+
+    let certificates = signed_data
+        .certificates
+        .iter()
+        .flat_map(|certificates| certificates.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(certificate) => Some(certificate),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let certificate = signed_data
+        .signer_infos
+        .0
+        .iter()
+        .next()
+        .and_then(|signer_info| match &signer_info.sid {
+            SignerIdentifier::IssuerAndSerialNumber(issuer_and_serial) => {
+                certificates.iter().find(|certificate| {
+                    certificate.tbs_certificate.issuer == issuer_and_serial.issuer
+                        && certificate.tbs_certificate.serial_number
+                            == issuer_and_serial.serial_number
+                })
+            }
+            SignerIdentifier::SubjectKeyIdentifier(_) => None,
+        })
+        .or_else(|| certificates.first())
+        .ok_or_eyre("签名中没有证书")?;
+
+    let mut signer = Signer::from(*certificate);
+    let names = certificates
+        .iter()
+        .map(|certificate| {
+            (
+                certificate.tbs_certificate.subject.to_string(),
+                certificate.tbs_certificate.issuer.to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    signer.chain = Signer::build_chain(
+        &signer.issuer,
+        names
+            .iter()
+            .map(|(subject, issuer)| (subject.as_str(), issuer.as_str())),
+    );
+
+    Ok(signer)
+}
+
+impl From<&Certificate> for Signer {
+    fn from(certificate: &Certificate) -> Self {
+        let to_date_time = |time: Time| {
+            i64::try_from(time.to_unix_duration().as_secs())
+                .ok()
+                .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+        };
+
+        let tbs_certificate = &certificate.tbs_certificate;
+        Self {
+            subject: tbs_certificate.subject.to_string(),
+            issuer: tbs_certificate.issuer.to_string(),
+            not_before: to_date_time(tbs_certificate.validity.not_before),
+            not_after: to_date_time(tbs_certificate.validity.not_after),
+            chain: Vec::new(),
+        }
+    }
+}
+
+/// Hashes every block of every file listed in the block map and returns the names of the files
+/// whose contents do not match.
+///
+/// <https://learn.microsoft.com/uwp/schemas/blockmapschema/app-package-block-map>
+fn verify_block_map<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Vec<String>> {
+    /// Files are split into 64 KiB blocks of uncompressed data
+    const BLOCK_SIZE: u64 = 1 << 16;
+
+    let block_map = from_str::<BlockMap>(&read_manifest(zip, APPX_BLOCK_MAP_XML)?)?;
+
+    // File names are percent-encoded in the package but not in the block map
+    let file_names = zip
+        .file_names()
+        .map(|name| {
+            (
+                percent_decode_str(name).decode_utf8_lossy().into_owned(),
+                name.to_owned(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut mismatches = Vec::new();
+    let mut buffer = Vec::with_capacity(BLOCK_SIZE as usize);
+    for file in block_map.files {
+        let Some(mut entry) = file_names
+            .get(&file.name.replace('\\', "/"))
+            .and_then(|name| zip.by_name(name).ok())
+        else {
+            mismatches.push(file.name);
+            continue;
+        };
+
+        let mut matches = true;
+        for block in &file.blocks {
+            buffer.clear();
+            entry.by_ref().take(BLOCK_SIZE).read_to_end(&mut buffer)?;
+            if Base64::encode_string(&Sha256::digest(&buffer)) != block.hash {
+                matches = false;
+                break;
+            }
+        }
+
+        // There should be no data left over after the last block
+        if !matches || entry.read(&mut [0])? != 0 {
+            mismatches.push(file.name);
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/blockmapschema/element-blockmap>
+#[derive(Deserialize)]
+struct BlockMap {
+    #[serde(default, rename = "File")]
+    files: Vec<File>,
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/blockmapschema/element-file>
+#[derive(Deserialize)]
+struct File {
+    #[serde(rename = "@Name")]
+    name: String,
+    #[serde(default, rename = "Block")]
+    blocks: Vec<Block>,
+}
+
+/// <https://learn.microsoft.com/uwp/schemas/blockmapschema/element-block>
+#[derive(Deserialize)]
+struct Block {
+    #[serde(rename = "@Hash")]
+    hash: String,
+}
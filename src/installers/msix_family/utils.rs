@@ -9,7 +9,13 @@ use package_family_name::PackageFamilyName;
 use winget_types::shared::Sha256String;
 use zip::ZipArchive;
 
-use crate::installers::{msix_family::APPX_SIGNATURE_P7X, utils::RELATIVE_PROGRAM_FILES_64};
+use crate::installers::{
+    msix_family::{APPX_BLOCK_MAP_XML, APPX_SIGNATURE_P7X},
+    utils::{
+        RELATIVE_PROGRAM_FILES_64,
+        release_date::{ReleaseDate, ReleaseDateSource},
+    },
+};
 
 pub fn read_manifest<R: Read + Seek>(zip: &mut ZipArchive<R>, path: &str) -> Result<String> {
     let mut appx_manifest_file = zip.by_name(path)?;
@@ -18,6 +24,17 @@ pub fn read_manifest<R: Read + Seek>(zip: &mut ZipArchive<R>, path: &str) -> Res
     Ok(appx_manifest)
 }
 
+/// Returns the date the block map was last modified, which is when the package was created or
+/// last signed.
+pub fn block_map_date<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Option<ReleaseDate> {
+    zip.by_name(APPX_BLOCK_MAP_XML)
+        .ok()?
+        .last_modified()
+        .and_then(|date_time| {
+            ReleaseDate::from_zip_date_time(date_time, ReleaseDateSource::BlockMap)
+        })
+}
+
 pub fn hash_signature<R: Read + Seek>(zip: &mut ZipArchive<R>) -> io::Result<Sha256String> {
     let signature_file = zip.by_name(APPX_SIGNATURE_P7X)?;
     Sha256String::from_reader(signature_file)
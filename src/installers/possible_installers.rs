@@ -6,6 +6,7 @@ use crate::installers::{
     msi::Msi,
     msix_family::{Msix, bundle::MsixBundle},
     nsis::Nsis,
+    squirrel::Squirrel,
 };
 
 pub enum PossibleInstaller {
@@ -16,6 +17,7 @@ pub enum PossibleInstaller {
     Zip(Vec<Installer>),
     Inno(Inno),
     Nsis(Nsis),
+    Squirrel(Squirrel),
     Other(Installer),
 }
 
@@ -29,6 +31,7 @@ impl PossibleInstaller {
             Self::Zip(installers) => installers,
             Self::Inno(inno) => inno.installers,
             Self::Nsis(nsis) => vec![nsis.installer],
+            Self::Squirrel(squirrel) => vec![squirrel.installer],
             Self::Other(installer) => vec![installer],
         }
     }
@@ -0,0 +1,278 @@
+use std::{
+    io,
+    io::{Cursor, Read, Seek},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use quick_xml::de::from_str;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::debug;
+use winget_types::{
+    installer::{
+        AppsAndFeaturesEntry, Architecture, InstallationMetadata, Installer, InstallerType, Scope,
+        UpgradeBehavior,
+        switches::{InstallerSwitches, SilentSwitch, SilentWithProgressSwitch},
+    },
+    shared::Version,
+};
+use yara_x::mods::{PE, pe::Resource};
+use zip::{ZipArchive, result::ZipError};
+
+use crate::{installers::utils::RELATIVE_LOCAL_APP_DATA, traits::FromMachine};
+
+#[derive(Error, Debug)]
+pub enum SquirrelError {
+    #[error("File is not a Squirrel installer")]
+    NotSquirrelFile,
+    #[error(transparent)]
+    Zip(#[from] ZipError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A Squirrel.Windows `Setup.exe`, as produced by Electron Forge and electron-builder's Squirrel
+/// target.
+///
+/// The setup executable embeds a zip containing `Update.exe` and the full NuGet package of the
+/// application, which is always installed for the current user into
+/// `%LocalAppData%\<package id>`.
+///
+/// <https://github.com/Squirrel/Squirrel.Windows/blob/develop/docs/using/install-process.md>
+pub struct Squirrel {
+    pub installer: Installer,
+    pub title: Option<String>,
+    pub authors: Option<String>,
+}
+
+impl Squirrel {
+    pub fn new(data: &[u8], pe: &PE) -> Result<Self, SquirrelError> {
+        const SILENT: &str = "--silent";
+
+        let resource = Self::get_update_zip_resource(pe).ok_or(SquirrelError::NotSquirrelFile)?;
+        let offset = resource.offset() as usize;
+        let update_zip = data
+            .get(offset..offset + resource.length() as usize)
+            .ok_or(SquirrelError::NotSquirrelFile)?;
+
+        // Other executables can have a resource with the same name, which won't be a zip containing
+        // a NuGet package, so anything unexpected falls through to the other EXE detection
+        let mut update_zip =
+            ZipArchive::new(Cursor::new(update_zip)).map_err(|_| SquirrelError::NotSquirrelFile)?;
+        let nupkg_name = update_zip
+            .file_names()
+            .find(|name| {
+                Utf8Path::new(name)
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case(NUPKG))
+            })
+            .map(str::to_owned)
+            .ok_or(SquirrelError::NotSquirrelFile)?;
+
+        let mut nupkg = Vec::new();
+        update_zip.by_name(&nupkg_name)?.read_to_end(&mut nupkg)?;
+        let mut nupkg =
+            ZipArchive::new(Cursor::new(nupkg)).map_err(|_| SquirrelError::NotSquirrelFile)?;
+
+        let nuspec = read_nuspec(&mut nupkg)?;
+        debug!(?nuspec);
+
+        let architecture = main_executable_machine(&mut nupkg)?
+            .unwrap_or_else(|| Architecture::from_machine(pe.machine()));
+
+        Ok(Self {
+            installer: Installer {
+                architecture,
+                r#type: Some(InstallerType::Exe),
+                scope: Some(Scope::User),
+                switches: Some(InstallerSwitches {
+                    silent: SILENT.parse::<SilentSwitch>().ok(),
+                    silent_with_progress: SILENT.parse::<SilentWithProgressSwitch>().ok(),
+                    ..InstallerSwitches::default()
+                }),
+                upgrade_behavior: Some(UpgradeBehavior::Install),
+                installation_metadata: Some(InstallationMetadata {
+                    default_install_location: Some(Utf8PathBuf::from(format!(
+                        r"{RELATIVE_LOCAL_APP_DATA}\{}",
+                        nuspec.metadata.id
+                    ))),
+                    ..InstallationMetadata::default()
+                }),
+                // Squirrel registers the uninstall entry under the package ID
+                apps_and_features_entries: Some(vec![AppsAndFeaturesEntry {
+                    display_name: nuspec.metadata.title.clone(),
+                    publisher: nuspec.metadata.authors.clone(),
+                    display_version: Some(Version::new(&nuspec.metadata.version)),
+                    product_code: Some(nuspec.metadata.id),
+                    ..AppsAndFeaturesEntry::default()
+                }]),
+                ..Installer::default()
+            },
+            title: nuspec.metadata.title,
+            authors: nuspec.metadata.authors,
+        })
+    }
+
+    /// Squirrel's `Setup.exe` stores the zip as resource 131 of the custom `DATA` type.
+    ///
+    /// <https://github.com/Squirrel/Squirrel.Windows/blob/develop/src/Setup/Setup.rc>
+    fn get_update_zip_resource(pe: &PE) -> Option<&Resource> {
+        const DATA: &[u8] = b"D\0A\0T\0A\0";
+        const IDR_UPDATE_ZIP: u32 = 131;
+
+        pe.resources
+            .iter()
+            .find(|resource| resource.type_string() == DATA && resource.id() == IDR_UPDATE_ZIP)
+    }
+}
+
+const NUPKG: &str = "nupkg";
+
+fn read_nuspec<R: Read + Seek>(nupkg: &mut ZipArchive<R>) -> Result<Nuspec, SquirrelError> {
+    const NUSPEC: &str = "nuspec";
+
+    let nuspec_name = nupkg
+        .file_names()
+        .find(|name| {
+            Utf8Path::new(name).parent() == Some(Utf8Path::new(""))
+                && Utf8Path::new(name)
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case(NUSPEC))
+        })
+        .map(str::to_owned)
+        .ok_or(SquirrelError::NotSquirrelFile)?;
+
+    let nuspec = nupkg
+        .by_name(&nuspec_name)
+        .ok()
+        .and_then(|file| io::read_to_string(file).ok())
+        .ok_or(SquirrelError::NotSquirrelFile)?;
+    from_str::<Nuspec>(nuspec.trim_start_matches('\u{FEFF}'))
+        .map_err(|_| SquirrelError::NotSquirrelFile)
+}
+
+/// Reads the machine type of the largest executable in the package's `lib` folder, which is the
+/// Electron application itself, as `Setup.exe` is always a 32-bit executable.
+fn main_executable_machine<R: Read + Seek>(
+    nupkg: &mut ZipArchive<R>,
+) -> Result<Option<Architecture>, SquirrelError> {
+    const EXE: &str = "exe";
+    const LIB: &str = "lib";
+    /// The offset of the PE header's offset in the DOS header
+    const E_LFANEW: usize = 0x3C;
+    const PE_HEADER_LEN: usize = 1 << 12;
+
+    let executables = nupkg
+        .file_names()
+        .map(Utf8Path::new)
+        .filter(|path| {
+            path.starts_with(LIB)
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case(EXE))
+        })
+        .map(Utf8Path::to_path_buf)
+        .collect::<Vec<_>>();
+
+    let mut main_executable = None;
+    let mut largest_size = 0;
+    for executable in executables {
+        let size = nupkg.by_name(executable.as_str())?.size();
+        if main_executable.is_none() || size > largest_size {
+            largest_size = size;
+            main_executable = Some(executable);
+        }
+    }
+    let Some(main_executable) = main_executable else {
+        return Ok(None);
+    };
+
+    let mut header = Vec::with_capacity(PE_HEADER_LEN);
+    nupkg
+        .by_name(main_executable.as_str())?
+        .take(PE_HEADER_LEN as u64)
+        .read_to_end(&mut header)?;
+
+    let machine = header
+        .get(E_LFANEW..E_LFANEW + size_of::<u32>())
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(|bytes| u32::from_le_bytes(bytes) as usize)
+        // The machine type follows the `PE\0\0` signature
+        .and_then(|pe_offset| header.get(pe_offset + 4..pe_offset + 6))
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u16::from_le_bytes);
+
+    Ok(match machine {
+        Some(0x014C) => Some(Architecture::X86),
+        Some(0x8664) => Some(Architecture::X64),
+        Some(0xAA64) => Some(Architecture::Arm64),
+        _ => None,
+    })
+}
+
+/// <https://learn.microsoft.com/nuget/reference/nuspec>
+#[derive(Debug, Deserialize)]
+struct Nuspec {
+    metadata: Metadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    id: String,
+    version: String,
+    title: Option<String>,
+    authors: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use indoc::indoc;
+    use rstest::rstest;
+    use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+    use crate::installers::squirrel::{SquirrelError, read_nuspec};
+
+    fn nupkg(name: &str, content: &str) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn nuspec() {
+        const NUSPEC: &str = indoc! {r#"
+            <?xml version="1.0" encoding="utf-8"?>
+            <package xmlns="http://schemas.microsoft.com/packaging/2010/07/nuspec.xsd">
+              <metadata>
+                <id>example-app</id>
+                <title>Example App</title>
+                <version>1.2.3</version>
+                <authors>Example Corp</authors>
+                <description>An example Electron app</description>
+              </metadata>
+            </package>
+        "#};
+
+        let nuspec = read_nuspec(&mut nupkg("example-app.nuspec", NUSPEC)).unwrap();
+        assert_eq!(nuspec.metadata.id, "example-app");
+        assert_eq!(nuspec.metadata.version, "1.2.3");
+        assert_eq!(nuspec.metadata.title.as_deref(), Some("Example App"));
+        assert_eq!(nuspec.metadata.authors.as_deref(), Some("Example Corp"));
+    }
+
+    #[rstest]
+    #[case::missing_nuspec("lib/net45/app.exe", "")]
+    #[case::nested_nuspec("lib/example-app.nuspec", "<package />")]
+    #[case::invalid_nuspec("example-app.nuspec", "<package><metadata /></package>")]
+    fn not_squirrel_package(#[case] name: &str, #[case] content: &str) {
+        assert!(matches!(
+            read_nuspec(&mut nupkg(name, content)),
+            Err(SquirrelError::NotSquirrelFile)
+        ));
+    }
+}
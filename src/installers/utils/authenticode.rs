@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use yara_x::mods::PE;
+
+use crate::installers::utils::signer::Signer;
+
+/// Reads the signer of the primary Authenticode signature of an executable.
+///
+/// <https://learn.microsoft.com/windows-hardware/drivers/install/authenticode>
+pub fn read_signer(pe: &PE) -> Option<Signer> {
+    let to_date_time = |seconds: Option<i64>| {
+        seconds.and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+    };
+
+    let signature = pe.signatures.first()?;
+    let subject = signature.subject.clone()?;
+    let issuer = signature.issuer.clone().unwrap_or_default();
+
+    let chain = Signer::build_chain(
+        &issuer,
+        signature
+            .certificates
+            .iter()
+            .map(|certificate| (certificate.subject(), certificate.issuer())),
+    );
+
+    Some(Signer {
+        subject,
+        issuer,
+        not_before: to_date_time(signature.not_before),
+        not_after: to_date_time(signature.not_after),
+        chain,
+    })
+}
@@ -1,5 +1,9 @@
+pub mod authenticode;
 pub mod lzma_stream_header;
 pub mod registry;
+pub mod release_date;
+pub mod runtime_dependencies;
+pub mod signer;
 
 pub const RELATIVE_PROGRAM_FILES_64: &str = "%ProgramFiles%";
 pub const RELATIVE_PROGRAM_FILES_32: &str = "%ProgramFiles(x86)%";
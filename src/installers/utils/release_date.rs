@@ -0,0 +1,99 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use derive_more::Display;
+use yara_x::mods::PE;
+
+/// Where the release date of an installer was found.
+#[derive(Copy, Clone, Debug, Display, Eq, PartialEq)]
+pub enum ReleaseDateSource {
+    #[display("Last-Modified 响应头")]
+    LastModified,
+    #[display("Authenticode 签名时间")]
+    SigningTime,
+    #[display("PE 时间戳")]
+    PeTimestamp,
+    #[display("MSI 摘要信息的创建时间")]
+    MsiCreationTime,
+    #[display("AppxBlockMap.xml 的修改时间")]
+    BlockMap,
+    #[display("zip 条目的最新修改时间")]
+    ZipEntry,
+}
+
+#[derive(Copy, Clone, Debug, Display, Eq, PartialEq)]
+#[display("{date} ({source})")]
+pub struct ReleaseDate {
+    pub date: NaiveDate,
+    pub source: ReleaseDateSource,
+}
+
+impl ReleaseDate {
+    pub const fn new(date: NaiveDate, source: ReleaseDateSource) -> Self {
+        Self { date, source }
+    }
+
+    /// Creates a release date from a date found inside an installer, discarding it if it is
+    /// implausible.
+    ///
+    /// Reproducible builds replace timestamps with a hash or a fixed value, and zip entries
+    /// without a date are stored as 1980-01-01.
+    fn from_payload(date: NaiveDate, source: ReleaseDateSource) -> Option<Self> {
+        const EARLIEST: NaiveDate = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+
+        (date >= EARLIEST && date <= Utc::now().date_naive()).then_some(Self::new(date, source))
+    }
+
+    pub fn from_timestamp(seconds: i64, source: ReleaseDateSource) -> Option<Self> {
+        DateTime::<Utc>::from_timestamp(seconds, 0)
+            .and_then(|date_time| Self::from_payload(date_time.date_naive(), source))
+    }
+
+    pub fn from_system_time(system_time: SystemTime, source: ReleaseDateSource) -> Option<Self> {
+        Self::from_payload(DateTime::<Utc>::from(system_time).date_naive(), source)
+    }
+
+    pub fn from_zip_date_time(date_time: zip::DateTime, source: ReleaseDateSource) -> Option<Self> {
+        NaiveDate::from_ymd_opt(
+            i32::from(date_time.year()),
+            u32::from(date_time.month()),
+            u32::from(date_time.day()),
+        )
+        .and_then(|date| Self::from_payload(date, source))
+    }
+
+    /// Uses the time the executable was signed, as the PE timestamp is unreliable when builds are
+    /// reproducible.
+    pub fn from_pe(pe: &PE) -> Option<Self> {
+        pe.signatures
+            .iter()
+            .flat_map(|signature| &signature.countersignatures)
+            .find_map(|countersignature| countersignature.sign_time)
+            .and_then(|sign_time| Self::from_timestamp(sign_time, ReleaseDateSource::SigningTime))
+            .or_else(|| {
+                pe.timestamp.and_then(|timestamp| {
+                    Self::from_timestamp(i64::from(timestamp), ReleaseDateSource::PeTimestamp)
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rstest::rstest;
+
+    use crate::installers::utils::release_date::{ReleaseDate, ReleaseDateSource};
+
+    #[rstest]
+    #[case(1_704_067_200, Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()))]
+    #[case(0, None)]
+    #[case(i64::from(u32::MAX), None)]
+    fn timestamp(#[case] seconds: i64, #[case] expected: Option<NaiveDate>) {
+        assert_eq!(
+            ReleaseDate::from_timestamp(seconds, ReleaseDateSource::PeTimestamp)
+                .map(|release_date| release_date.date),
+            expected
+        );
+    }
+}
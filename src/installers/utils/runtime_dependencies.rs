@@ -0,0 +1,356 @@
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use tracing::debug;
+use winget_types::{
+    installer::{Architecture, Dependencies, Installer, PackageDependencies},
+    shared::PackageVersion,
+};
+use yara_x::mods::PE;
+
+use crate::traits::FromMachine;
+
+/// Visual C++ runtime libraries mapped to the version of the redistributable that provides them.
+///
+/// Every version from 2015 onwards is binary compatible and shares the same libraries.
+const VC_RUNTIMES: [(&str, &str); 8] = [
+    ("vcruntime140", "2015+"),
+    ("msvcp140", "2015+"),
+    ("msvcr120", "2013"),
+    ("msvcp120", "2013"),
+    ("msvcr110", "2012"),
+    ("msvcp110", "2012"),
+    ("msvcr100", "2010"),
+    ("msvcp100", "2010"),
+];
+
+/// Shared .NET frameworks mapped to the winget package identifier prefix of their runtime.
+///
+/// <https://learn.microsoft.com/dotnet/core/deploying/#framework-dependent-deployment>
+const DOTNET_FRAMEWORKS: [(&str, &str); 3] = [
+    (WINDOWS_DESKTOP_APP, "Microsoft.DotNet.DesktopRuntime."),
+    ("Microsoft.AspNetCore.App", "Microsoft.DotNet.AspNetCore."),
+    (NETCORE_APP, "Microsoft.DotNet.Runtime."),
+];
+
+const WINDOWS_DESKTOP_APP: &str = "Microsoft.WindowsDesktop.App";
+const NETCORE_APP: &str = "Microsoft.NETCore.App";
+
+const VC_REDIST: &str = "Microsoft.VCRedist.";
+
+/// The index of the CLR runtime header in the data directories, which is only present in managed
+/// executables.
+///
+/// <https://learn.microsoft.com/windows/win32/debug/pe-format#optional-header-data-directories-image-only>
+const CLR_RUNTIME_HEADER: usize = 14;
+
+/// The Windows feature that provides .NET Framework 2.0 to 3.5.
+const NET_FX_3: &str = "NetFx3";
+
+/// Returns the Visual C++ redistributables an executable needs based on the libraries it imports.
+pub fn from_imports(pe: &PE) -> BTreeSet<PackageDependencies> {
+    let architecture = match Architecture::from_machine(pe.machine()) {
+        Architecture::X86 => "x86",
+        Architecture::X64 => "x64",
+        Architecture::Arm64 => "arm64",
+        _ => return BTreeSet::new(),
+    };
+
+    pe.import_details
+        .iter()
+        .chain(&pe.delayed_import_details)
+        .filter_map(|import| import.library_name.as_deref())
+        .filter_map(|library_name| {
+            let library_name = library_name.to_ascii_lowercase();
+            VC_RUNTIMES
+                .iter()
+                .find(|(runtime, _)| library_name.starts_with(runtime))
+                .map(|(_, version)| *version)
+        })
+        // Only the 2015+ redistributable is published for ARM64
+        .filter(|&version| architecture != "arm64" || version == "2015+")
+        .filter_map(|version| {
+            Some(PackageDependencies {
+                package_identifier: format!("{VC_REDIST}{version}.{architecture}")
+                    .parse()
+                    .ok()?,
+                minimum_version: None,
+            })
+        })
+        .collect()
+}
+
+/// Returns the Windows features a .NET Framework executable needs based on the runtime version in
+/// the metadata its CLR header points to.
+///
+/// .NET Framework 4 is included in every version of Windows that WinGet supports, so only
+/// executables built for the 2.0 runtime, which is an optional feature, need anything installed.
+pub fn from_clr_header(data: &[u8], pe: &PE) -> BTreeSet<String> {
+    /// The offset of the metadata directory within `IMAGE_COR20_HEADER`
+    const METADATA_OFFSET: usize = 8;
+    const NET_FX_3_RUNTIMES: [&str; 2] = ["v1.", "v2."];
+
+    let rva_to_offset = |rva: u32| {
+        pe.sections
+            .iter()
+            .find(|section| {
+                let size = section.virtual_size().max(section.raw_data_size());
+                (section.virtual_address()..section.virtual_address().saturating_add(size))
+                    .contains(&rva)
+            })
+            .and_then(|section| {
+                (rva - section.virtual_address()).checked_add(section.raw_data_offset())
+            })
+            .and_then(|offset| usize::try_from(offset).ok())
+    };
+
+    let runtime_version = pe
+        .data_directories
+        .get(CLR_RUNTIME_HEADER)
+        .filter(|directory| directory.virtual_address() != 0)
+        .and_then(|directory| rva_to_offset(directory.virtual_address()))
+        .and_then(|header| read_u32(data, header + METADATA_OFFSET))
+        .and_then(rva_to_offset)
+        .and_then(|metadata| data.get(metadata..))
+        .and_then(metadata_runtime_version);
+
+    debug!(?runtime_version);
+
+    runtime_version
+        .filter(|version| {
+            NET_FX_3_RUNTIMES
+                .iter()
+                .any(|runtime| version.starts_with(runtime))
+        })
+        .map(|_| BTreeSet::from([NET_FX_3.to_owned()]))
+        .unwrap_or_default()
+}
+
+/// Reads the version of the runtime an assembly was built for, such as `v4.0.30319`, from the
+/// root of its metadata.
+///
+/// <https://www.ecma-international.org/publications-and-standards/standards/ecma-335/> II.24.2.1
+fn metadata_runtime_version(metadata: &[u8]) -> Option<&str> {
+    const SIGNATURE: &[u8] = b"BSJB";
+    const LENGTH_OFFSET: usize = 12;
+    const VERSION_OFFSET: usize = 16;
+
+    if !metadata.starts_with(SIGNATURE) {
+        return None;
+    }
+    let length = usize::try_from(read_u32(metadata, LENGTH_OFFSET)?).ok()?;
+    let version = metadata.get(VERSION_OFFSET..VERSION_OFFSET.checked_add(length)?)?;
+    std::str::from_utf8(version)
+        .ok()
+        .map(|version| version.trim_end_matches('\0'))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(size_of::<u32>())?)?
+        .try_into()
+        .ok()
+        .map(u32::from_le_bytes)
+}
+
+/// Returns the shared .NET runtimes a framework-dependent application needs from its
+/// `runtimeconfig.json`.
+///
+/// Self-contained applications list their bundled frameworks under `includedFrameworks` instead,
+/// so they have no runtime dependencies.
+///
+/// <https://learn.microsoft.com/dotnet/core/runtime-config/#runtimeconfigjson>
+pub fn from_runtime_config(runtime_config: &[u8]) -> BTreeSet<PackageDependencies> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RuntimeConfig {
+        runtime_options: RuntimeOptions,
+    }
+
+    #[derive(Deserialize)]
+    struct RuntimeOptions {
+        framework: Option<Framework>,
+        #[serde(default)]
+        frameworks: Vec<Framework>,
+    }
+
+    #[derive(Deserialize)]
+    struct Framework {
+        name: String,
+        version: String,
+    }
+
+    let runtime_config = match serde_json::from_slice::<RuntimeConfig>(runtime_config) {
+        Ok(runtime_config) => runtime_config,
+        Err(error) => {
+            debug!(%error, "Failed to parse runtimeconfig.json");
+            return BTreeSet::new();
+        }
+    };
+
+    let frameworks = runtime_config
+        .runtime_options
+        .framework
+        .into_iter()
+        .chain(runtime_config.runtime_options.frameworks)
+        .collect::<Vec<_>>();
+
+    // The Windows Desktop Runtime installer also installs the .NET Runtime it depends on
+    let has_desktop_runtime = frameworks
+        .iter()
+        .any(|framework| framework.name == WINDOWS_DESKTOP_APP);
+
+    frameworks
+        .into_iter()
+        .filter(|framework| !(has_desktop_runtime && framework.name == NETCORE_APP))
+        .filter_map(|framework| {
+            let (_, identifier) = DOTNET_FRAMEWORKS
+                .iter()
+                .find(|(name, _)| *name == framework.name)?;
+            let (major, _) = framework.version.split_once('.')?;
+            Some(PackageDependencies {
+                package_identifier: format!("{identifier}{major}").parse().ok()?,
+                minimum_version: framework.version.parse::<PackageVersion>().ok(),
+            })
+        })
+        .collect()
+}
+
+/// Removes any Visual C++ redistributable dependencies from an installer, such as when the
+/// runtime libraries are deployed alongside the application.
+pub fn remove_vc_redist(installer: &mut Installer) {
+    let Some(dependencies) = installer.dependencies.as_mut() else {
+        return;
+    };
+
+    if let Some(package_dependencies) = dependencies.package_dependencies.as_mut() {
+        package_dependencies.retain(|dependency| {
+            !dependency
+                .package_identifier
+                .as_str()
+                .starts_with(VC_REDIST)
+        });
+        if package_dependencies.is_empty() {
+            dependencies.package_dependencies = None;
+        }
+    }
+
+    if *dependencies == Dependencies::default() {
+        installer.dependencies = None;
+    }
+}
+
+/// Adds Windows features to an installer's dependencies, keeping any it already has.
+pub fn insert_windows_features(installer: &mut Installer, windows_features: BTreeSet<String>) {
+    if windows_features.is_empty() {
+        return;
+    }
+
+    installer
+        .dependencies
+        .get_or_insert_with(Dependencies::default)
+        .windows_features
+        .get_or_insert_default()
+        .extend(windows_features);
+}
+
+/// Adds package dependencies to an installer, keeping any it already has.
+pub fn insert(installer: &mut Installer, package_dependencies: BTreeSet<PackageDependencies>) {
+    if package_dependencies.is_empty() {
+        return;
+    }
+
+    installer
+        .dependencies
+        .get_or_insert_with(Dependencies::default)
+        .package_dependencies
+        .get_or_insert_default()
+        .extend(package_dependencies);
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rstest::rstest;
+
+    use crate::installers::utils::runtime_dependencies::{
+        from_runtime_config, metadata_runtime_version,
+    };
+
+    #[rstest]
+    #[case(b"v4.0.30319\0\0", Some("v4.0.30319"))]
+    #[case(b"v2.0.50727\0\0", Some("v2.0.50727"))]
+    fn runtime_version(#[case] version: &[u8; 12], #[case] expected: Option<&str>) {
+        let mut metadata = b"BSJB".to_vec();
+        metadata.extend_from_slice(&1_u16.to_le_bytes());
+        metadata.extend_from_slice(&1_u16.to_le_bytes());
+        metadata.extend_from_slice(&0_u32.to_le_bytes());
+        metadata.extend_from_slice(&12_u32.to_le_bytes());
+        metadata.extend_from_slice(version);
+        assert_eq!(metadata_runtime_version(&metadata), expected);
+    }
+
+    #[test]
+    fn not_metadata() {
+        assert_eq!(metadata_runtime_version(b"MZ\x90\0"), None);
+    }
+
+    #[rstest]
+    #[case(
+        indoc! {r#"
+            {
+              "runtimeOptions": {
+                "tfm": "net8.0",
+                "frameworks": [
+                  { "name": "Microsoft.NETCore.App", "version": "8.0.0" },
+                  { "name": "Microsoft.WindowsDesktop.App", "version": "8.0.0" }
+                ]
+              }
+            }
+        "#},
+        &[("Microsoft.DotNet.DesktopRuntime.8", "8.0.0")]
+    )]
+    #[case(
+        indoc! {r#"
+            {
+              "runtimeOptions": {
+                "tfm": "net6.0",
+                "framework": { "name": "Microsoft.NETCore.App", "version": "6.0.2" }
+              }
+            }
+        "#},
+        &[("Microsoft.DotNet.Runtime.6", "6.0.2")]
+    )]
+    #[case(
+        indoc! {r#"
+            {
+              "runtimeOptions": {
+                "tfm": "net8.0",
+                "includedFrameworks": [
+                  { "name": "Microsoft.NETCore.App", "version": "8.0.4" }
+                ]
+              }
+            }
+        "#},
+        &[]
+    )]
+    fn runtime_config(#[case] runtime_config: &str, #[case] expected: &[(&str, &str)]) {
+        let dependencies = from_runtime_config(runtime_config.as_bytes());
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|dependency| (
+                    dependency.package_identifier.to_string(),
+                    dependency
+                        .minimum_version
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default()
+                ))
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|&(identifier, version)| (identifier.to_owned(), version.to_owned()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::mem;
+
+use chrono::{DateTime, Utc};
+
+/// The subject and validity period of the certificate that signed an installer.
+pub struct Signer {
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    /// The subjects of the certificates that issued the signing certificate, up to the root.
+    pub chain: Vec<String>,
+}
+
+impl Signer {
+    /// Returns whether the signer's subject is the same distinguished name as `name`.
+    ///
+    /// The order of the relative distinguished names, whitespace around separators and the case
+    /// of attribute types are ignored. `ST` and `S` are treated as the same attribute as
+    /// `SignTool` and MSIX manifests use `S` for the state or province.
+    pub fn is_subject(&self, name: &str) -> bool {
+        normalize_distinguished_name(&self.subject) == normalize_distinguished_name(name)
+    }
+
+    /// Returns whether the signer's common name or organization is the same as a publisher's
+    /// display name, ignoring case, punctuation and legal suffixes such as `Inc.` or `Ltd.`.
+    pub fn is_publisher(&self, publisher: &str) -> bool {
+        let publisher = normalize_organization(publisher);
+        !publisher.is_empty()
+            && normalize_distinguished_name(&self.subject)
+                .into_iter()
+                .filter(|(key, _)| key == "CN" || key == "O")
+                .any(|(_, value)| normalize_organization(&value) == publisher)
+    }
+
+    /// Builds the chain of issuers of a certificate from the subjects and issuers of all the
+    /// certificates included in a signature.
+    pub fn build_chain<'a, I>(issuer: &str, certificates: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let certificates = certificates.into_iter().collect::<Vec<_>>();
+        let mut chain = Vec::new();
+        let mut issuer = issuer;
+        while let Some(&(subject, next_issuer)) =
+            certificates.iter().find(|(subject, _)| *subject == issuer)
+        {
+            // Guard against cycles, which also stops at a self-signed root
+            if chain.iter().any(|existing| existing == subject) {
+                break;
+            }
+            chain.push(subject.to_owned());
+            issuer = next_issuer;
+        }
+        chain
+    }
+}
+
+fn normalize_organization(name: &str) -> String {
+    const LEGAL_SUFFIXES: [&str; 11] = [
+        "inc",
+        "incorporated",
+        "llc",
+        "ltd",
+        "limited",
+        "corp",
+        "corporation",
+        "co",
+        "gmbh",
+        "ag",
+        "bv",
+    ];
+
+    name.split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !LEGAL_SUFFIXES.contains(&word.as_str()))
+        .collect()
+}
+
+/// Splits a distinguished name into its sorted attributes.
+///
+/// Both the RFC 4514 form (`CN=Contoso, C=US`) and the OpenSSL form that Authenticode subjects are
+/// reported in (`/C=US/CN=Contoso`) are accepted.
+fn normalize_distinguished_name(name: &str) -> Vec<(String, String)> {
+    let separators: &[char] = if name.trim_start().starts_with('/') {
+        &['/']
+    } else {
+        &[',', ';']
+    };
+    let mut attributes = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = name.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            // Certificates escape special characters such as `\,` (RFC 4514) where MSIX manifests
+            // quote the whole value instead, so both are reduced to the plain value
+            '\\' => current.extend(chars.next()),
+            '"' => in_quotes = !in_quotes,
+            char if separators.contains(&char) && !in_quotes => {
+                attributes.push(mem::take(&mut current));
+            }
+            _ => current.push(char),
+        }
+    }
+    attributes.push(current);
+
+    let mut attributes = attributes
+        .iter()
+        .filter_map(|attribute| attribute.split_once('='))
+        .map(|(key, value)| {
+            let mut key = key.trim().to_ascii_uppercase();
+            if key == "ST" {
+                key = String::from("S");
+            }
+            (key, value.trim().to_owned())
+        })
+        .collect::<Vec<_>>();
+    attributes.sort_unstable();
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::installers::utils::signer::Signer;
+
+    #[rstest]
+    #[case(
+        "CN=Microsoft Corporation, O=Microsoft Corporation, L=Redmond, S=Washington, C=US",
+        "CN=Microsoft Corporation,O=Microsoft Corporation,L=Redmond,ST=Washington,C=US"
+    )]
+    #[case("CN=Contoso", "cn=Contoso")]
+    #[case(
+        r#"CN="Contoso, Ltd.", O=Contoso, C=US"#,
+        r#"C=US, O=Contoso, CN="Contoso, Ltd.""#
+    )]
+    #[case(
+        "/C=US/ST=Washington/L=Redmond/O=Microsoft Corporation/CN=Microsoft Corporation",
+        "CN=Microsoft Corporation, O=Microsoft Corporation, L=Redmond, S=Washington, C=US"
+    )]
+    #[case(
+        r"CN=Contoso\, Ltd., O=Contoso, C=US",
+        r#"CN="Contoso, Ltd.", O=Contoso, C=US"#
+    )]
+    fn matching_subjects(#[case] subject: &str, #[case] publisher: &str) {
+        let signer = Signer {
+            subject: subject.to_owned(),
+            issuer: String::new(),
+            not_before: None,
+            not_after: None,
+            chain: Vec::new(),
+        };
+        assert!(signer.is_subject(publisher));
+    }
+
+    #[rstest]
+    #[case("CN=Contoso", "CN=Fabrikam")]
+    #[case("CN=Contoso, O=Contoso", "CN=Contoso")]
+    #[case(r#"CN="Contoso, Ltd.""#, "CN=Contoso, Ltd.")]
+    fn mismatched_subjects(#[case] subject: &str, #[case] publisher: &str) {
+        let signer = Signer {
+            subject: subject.to_owned(),
+            issuer: String::new(),
+            not_before: None,
+            not_after: None,
+            chain: Vec::new(),
+        };
+        assert!(!signer.is_subject(publisher));
+    }
+
+    #[rstest]
+    #[case(
+        "CN=Microsoft Corporation, O=Microsoft Corporation, L=Redmond, C=US",
+        "Microsoft Corporation"
+    )]
+    #[case("CN=Contoso Ltd., O=Contoso Ltd., C=US", "Contoso")]
+    #[case("CN=Fabrikam, Inc.", "fabrikam")]
+    #[case(
+        "/C=US/ST=Washington/L=Redmond/O=Microsoft Corporation/CN=Microsoft Corporation",
+        "Microsoft Corporation"
+    )]
+    #[case("/C=US/O=Contoso, Ltd./CN=Contoso, Ltd.", "Contoso")]
+    fn matching_publishers(#[case] subject: &str, #[case] publisher: &str) {
+        let signer = Signer {
+            subject: subject.to_owned(),
+            issuer: String::new(),
+            not_before: None,
+            not_after: None,
+            chain: Vec::new(),
+        };
+        assert!(signer.is_publisher(publisher));
+    }
+
+    #[test]
+    fn chain() {
+        let certificates = [
+            ("CN=Contoso", "CN=Intermediate CA"),
+            ("CN=Root CA", "CN=Root CA"),
+            ("CN=Intermediate CA", "CN=Root CA"),
+        ];
+        assert_eq!(
+            Signer::build_chain("CN=Intermediate CA", certificates),
+            ["CN=Intermediate CA", "CN=Root CA"]
+        );
+    }
+}
@@ -8,11 +8,25 @@ use std::{
 use camino::{Utf8Path, Utf8PathBuf};
 use color_eyre::eyre::Result;
 use inquire::{MultiSelect, Text, min_length};
+use itertools::Itertools;
 use memmap2::Mmap;
-use winget_types::installer::{Installer, InstallerType, NestedInstallerFiles};
+use tracing::warn;
+use winget_types::{
+    installer::{
+        Architecture, Installer, InstallerType, NestedInstallerFiles, NestedInstallerType,
+    },
+    shared::PackageIdentifier,
+};
 use zip::ZipArchive;
 
-use crate::{file_analyser::FileAnalyser, prompts::handle_inquire_error};
+use crate::{
+    file_analyser::{FileAnalyser, ZIP, to_command_alias},
+    installers::utils::{
+        release_date::{ReleaseDate, ReleaseDateSource},
+        runtime_dependencies,
+    },
+    prompts::handle_inquire_error,
+};
 
 const VALID_NESTED_FILE_EXTENSIONS: [&str; 6] =
     ["msix", "msi", "appx", "exe", "msixbundle", "appxbundle"];
@@ -23,12 +37,20 @@ pub struct Zip<R: Read + Seek> {
     archive: ZipArchive<R>,
     pub possible_installer_files: Vec<Utf8PathBuf>,
     pub installers: Vec<Installer>,
+    pub release_date: Option<ReleaseDate>,
 }
 
 impl<R: Read + Seek> Zip<R> {
     pub fn new(reader: R) -> Result<Self> {
         let mut zip = ZipArchive::new(reader)?;
 
+        let release_date = (0..zip.len())
+            .filter_map(|index| zip.by_index_raw(index).ok()?.last_modified())
+            .filter_map(|date_time| {
+                ReleaseDate::from_zip_date_time(date_time, ReleaseDateSource::ZipEntry)
+            })
+            .max_by_key(|release_date| release_date.date);
+
         let possible_installer_files = zip
             .file_names()
             .map(Utf8Path::new)
@@ -47,6 +69,29 @@ impl<R: Read + Seek> Zip<R> {
             .map(Utf8Path::to_path_buf)
             .collect::<Vec<_>>();
 
+        // WinGet only extracts the outer archive, so installers inside a nested archive can't be
+        // pointed at by NestedInstallerFiles
+        let nested_archives = zip
+            .file_names()
+            .map(Utf8Path::new)
+            .filter(|file_name| {
+                file_name
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case(ZIP))
+            })
+            .filter(|file_name| {
+                file_name
+                    .components()
+                    .all(|component| component.as_str() != MACOS_X_FOLDER)
+            })
+            .collect::<Vec<_>>();
+        if !nested_archives.is_empty() {
+            warn!(
+                "WinGet 无法安装压缩包中嵌套的压缩包，以下文件不会被分析: {}",
+                nested_archives.iter().join(", ")
+            );
+        }
+
         let installer_type_counts = VALID_NESTED_FILE_EXTENSIONS
             .iter()
             .map(|file_extension| {
@@ -64,57 +109,58 @@ impl<R: Read + Seek> Zip<R> {
             })
             .collect::<HashMap<_, _>>();
 
-        let mut nested_installer_files = None;
-        let mut installers = None;
+        let mut zip = Self {
+            archive: zip,
+            possible_installer_files,
+            installers: vec![Installer {
+                r#type: Some(InstallerType::Zip),
+                ..Installer::default()
+            }],
+            release_date,
+        };
 
-        // If there's only one valid file in the zip, extract and analyse it
-        if installer_type_counts
-            .values()
-            .filter(|&&count| count == 1)
-            .count()
-            == 1
+        let chosen_files = if Self::is_side_by_side(&zip.possible_installer_files) {
+            // Builds for different architectures next to each other each get their own installer
+            zip.possible_installer_files.clone()
+        } else if let Ok(file_extension) = installer_type_counts
+            .iter()
+            .filter(|&(_, &count)| count == 1)
+            .exactly_one()
+            .map(|(file_extension, _)| file_extension)
         {
-            let chosen_file_name = &possible_installer_files[0];
-            nested_installer_files = Some(BTreeSet::from([NestedInstallerFiles {
-                relative_file_path: chosen_file_name.clone(),
-                portable_command_alias: None,
-            }]));
-            if let Ok(mut chosen_file) = zip.by_name(chosen_file_name.as_str()) {
-                let mut temp_file = tempfile::tempfile()?;
-                io::copy(&mut chosen_file, &mut temp_file)?;
-                let map = unsafe { Mmap::map(&temp_file) }?;
-                let file_analyser = FileAnalyser::new(&map, chosen_file_name.as_str())?;
-                installers = Some(
-                    file_analyser
-                        .installers
-                        .into_iter()
-                        .map(|installer| Installer {
-                            r#type: Some(InstallerType::Zip),
-                            nested_installer_type: installer
-                                .r#type
-                                .and_then(InstallerType::to_nested),
-                            nested_installer_files: nested_installer_files.clone(),
-                            ..installer
-                        })
-                        .collect::<Vec<_>>(),
-                );
+            // If there's only one valid file of a type in the zip, extract and analyse it
+            zip.possible_installer_files
+                .iter()
+                .filter(|file_name| {
+                    file_name
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case(file_extension))
+                })
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        if !chosen_files.is_empty() {
+            let mut nested_files = zip.analyse_nested_files(chosen_files)?;
+            for nested_file in &mut nested_files {
+                if nested_file.is_portable() {
+                    nested_file.nested_installer_files.portable_command_alias =
+                        nested_file.command_alias.take();
+                }
             }
+            zip.installers = Self::nested_installers(nested_files);
         }
 
-        Ok(Self {
-            archive: zip,
-            possible_installer_files,
-            installers: installers.unwrap_or_else(|| {
-                vec![Installer {
-                    r#type: Some(InstallerType::Zip),
-                    nested_installer_files,
-                    ..Installer::default()
-                }]
-            }),
-        })
+        Ok(zip)
     }
 
-    pub fn prompt(&mut self) -> Result<()> {
+    /// Asks which nested files to use, and the command alias of each portable file.
+    ///
+    /// The alias defaults to the one from the file's version info, falling back to the last
+    /// segment of the package identifier.
+    pub fn prompt(&mut self, identifier: &PackageIdentifier) -> Result<()> {
         if !&self.possible_installer_files.is_empty() {
             let chosen = MultiSelect::new(
                 "选择嵌套的文件",
@@ -123,49 +169,207 @@ impl<R: Read + Seek> Zip<R> {
             .with_validator(min_length!(1))
             .prompt()
             .map_err(handle_inquire_error)?;
-            let first_choice = chosen.first().unwrap();
-            let mut temp_file = tempfile::tempfile()?;
-            io::copy(
-                &mut self.archive.by_name(first_choice.as_str())?,
-                &mut temp_file,
-            )?;
-            let map = unsafe { Mmap::map(&temp_file) }?;
-            let file_analyser = FileAnalyser::new(&map, first_choice.file_name().unwrap())?;
-            let nested_installer_files = Some(
-                chosen
-                    .into_iter()
-                    .map(|path| {
-                        Ok(NestedInstallerFiles {
-                            portable_command_alias: if file_analyser.installers[0].r#type
-                                == Some(InstallerType::Portable)
-                            {
-                                Some(
-                                    Text::new(&format!(
-                                        "{} 的便携式命令/别名:", // 翻译不确定
-                                        path.as_str()
-                                    ))
-                                    .prompt()
-                                    .map_err(handle_inquire_error)?,
-                                )
-                                .filter(|alias| !alias.trim().is_empty())
-                            } else {
-                                None
-                            },
-                            relative_file_path: path,
-                        })
-                    })
-                    .collect::<Result<BTreeSet<_>>>()?,
-            );
-            self.installers = file_analyser
-                .installers
-                .into_iter()
-                .map(|installer| Installer {
-                    nested_installer_type: installer.r#type.and_then(InstallerType::to_nested),
-                    nested_installer_files: nested_installer_files.clone(),
-                    ..installer
+            let mut nested_files = self.analyse_nested_files(chosen)?;
+            for nested_file in nested_files
+                .iter_mut()
+                .filter(|nested_file| nested_file.is_portable())
+            {
+                let message = format!(
+                    "{} 的便携式命令/别名:", // 翻译不确定
+                    nested_file
+                        .nested_installer_files
+                        .relative_file_path
+                        .as_str()
+                );
+                let command_alias = nested_file.command_alias.take().or_else(|| {
+                    identifier
+                        .as_str()
+                        .rsplit('.')
+                        .next()
+                        .and_then(to_command_alias)
+                });
+                let mut alias_prompt = Text::new(&message);
+                if let Some(command_alias) = command_alias.as_deref() {
+                    alias_prompt = alias_prompt.with_default(command_alias);
+                }
+                nested_file.nested_installer_files.portable_command_alias =
+                    Some(alias_prompt.prompt().map_err(handle_inquire_error)?)
+                        .filter(|alias| !alias.trim().is_empty());
+            }
+            self.installers = Self::nested_installers(nested_files);
+        }
+        Ok(())
+    }
+
+    /// Extracts and analyses each of the chosen files.
+    ///
+    /// A nested file is analysed the same way as a downloaded file. Nested archives are not offered
+    /// and are reported when the zip is opened instead.
+    fn analyse_nested_files(&mut self, chosen: Vec<Utf8PathBuf>) -> Result<Vec<NestedFile>> {
+        chosen
+            .into_iter()
+            .map(|path| {
+                let mut temp_file = tempfile::tempfile()?;
+                io::copy(&mut self.archive.by_name(path.as_str())?, &mut temp_file)?;
+                let map = unsafe { Mmap::map(&temp_file) }?;
+                let mut file_analyser =
+                    FileAnalyser::new(&map, path.file_name().unwrap_or_else(|| path.as_str()))?;
+                let has_adjacent_libraries = self.has_adjacent_libraries(&path);
+                self.add_runtime_dependencies(&path, &mut file_analyser.installers)?;
+
+                // An alias that is the same as the file name is redundant
+                let command_alias = file_analyser.command_alias.filter(|alias| {
+                    path.file_stem()
+                        .is_none_or(|file_stem| !file_stem.eq_ignore_ascii_case(alias))
+                });
+
+                Ok(NestedFile {
+                    nested_installer_files: NestedInstallerFiles {
+                        relative_file_path: path,
+                        portable_command_alias: None,
+                    },
+                    installers: file_analyser.installers,
+                    command_alias,
+                    has_adjacent_libraries,
                 })
-                .collect();
+            })
+            .collect()
+    }
+
+    /// Returns whether there are DLLs in the same directory as the given file, which a portable
+    /// executable would need to be run from its extracted location rather than a symlink to load.
+    fn has_adjacent_libraries(&self, path: &Utf8Path) -> bool {
+        const DLL: &str = "dll";
+
+        self.archive
+            .file_names()
+            .map(Utf8Path::new)
+            .any(|file_name| {
+                file_name.parent() == path.parent()
+                    && file_name
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case(DLL))
+            })
+    }
+
+    /// Adds the .NET runtimes a framework-dependent executable needs from the `runtimeconfig.json`
+    /// next to it, and removes the Visual C++ redistributable if its libraries are shipped
+    /// alongside the executable.
+    fn add_runtime_dependencies(
+        &mut self,
+        path: &Utf8Path,
+        installers: &mut [Installer],
+    ) -> Result<()> {
+        const RUNTIME_CONFIG_JSON: &str = "runtimeconfig.json";
+        const VC_RUNTIME_LIBRARIES: [&str; 3] = ["vcruntime", "msvcp", "msvcr"];
+
+        let runtime_config_path = path.with_extension(RUNTIME_CONFIG_JSON);
+        if let Ok(mut runtime_config_file) = self.archive.by_name(runtime_config_path.as_str()) {
+            let mut runtime_config = Vec::new();
+            runtime_config_file.read_to_end(&mut runtime_config)?;
+            let dependencies = runtime_dependencies::from_runtime_config(&runtime_config);
+            for installer in &mut *installers {
+                runtime_dependencies::insert(installer, dependencies.clone());
+            }
         }
+
+        let has_app_local_vc_runtime =
+            self.archive
+                .file_names()
+                .map(Utf8Path::new)
+                .any(|file_name| {
+                    file_name.parent() == path.parent()
+                        && file_name.file_name().is_some_and(|file_name| {
+                            let file_name = file_name.to_ascii_lowercase();
+                            VC_RUNTIME_LIBRARIES
+                                .iter()
+                                .any(|library| file_name.starts_with(library))
+                        })
+                });
+        if has_app_local_vc_runtime {
+            installers
+                .iter_mut()
+                .for_each(runtime_dependencies::remove_vc_redist);
+        }
+
         Ok(())
     }
+
+    /// Creates a zip installer for each analysed nested file.
+    ///
+    /// Portable files with the same architecture are combined into a single installer, as a zip
+    /// installer can contain multiple portable files but only one other nested installer.
+    fn nested_installers(nested_files: Vec<NestedFile>) -> Vec<Installer> {
+        let mut installers = Vec::<Installer>::new();
+        for nested_file in nested_files {
+            let nested_installer_files = nested_file.nested_installer_files;
+            let architecture_in_path =
+                Architecture::from_url(nested_installer_files.relative_file_path.as_str());
+            for installer in nested_file.installers {
+                let architecture = architecture_in_path.unwrap_or(installer.architecture);
+                let nested_installer_type = installer.r#type.and_then(InstallerType::to_nested);
+                let archive_binaries_depend_on_path = (nested_installer_type
+                    == Some(NestedInstallerType::Portable)
+                    && nested_file.has_adjacent_libraries)
+                    .then_some(true);
+                if nested_installer_type == Some(NestedInstallerType::Portable) {
+                    if let Some(existing) = installers.iter_mut().find(|existing| {
+                        existing.architecture == architecture
+                            && existing.nested_installer_type == nested_installer_type
+                    }) {
+                        existing
+                            .nested_installer_files
+                            .get_or_insert_default()
+                            .insert(nested_installer_files.clone());
+                        existing.archive_binaries_depend_on_path = existing
+                            .archive_binaries_depend_on_path
+                            .or(archive_binaries_depend_on_path);
+                        if let Some(package_dependencies) = installer
+                            .dependencies
+                            .and_then(|dependencies| dependencies.package_dependencies)
+                        {
+                            runtime_dependencies::insert(existing, package_dependencies);
+                        }
+                        continue;
+                    }
+                }
+                installers.push(Installer {
+                    architecture,
+                    r#type: Some(InstallerType::Zip),
+                    nested_installer_type,
+                    nested_installer_files: Some(BTreeSet::from([nested_installer_files.clone()])),
+                    archive_binaries_depend_on_path,
+                    ..installer
+                });
+            }
+        }
+        installers
+    }
+
+    /// Returns whether the files are the same kind of installer built for different architectures,
+    /// such as `app-x64.exe` and `app-arm64.exe`.
+    fn is_side_by_side(files: &[Utf8PathBuf]) -> bool {
+        files.len() > 1
+            && files.iter().map(|file| file.extension()).all_equal()
+            && files
+                .iter()
+                .map(|file| Architecture::from_url(file.as_str()))
+                .collect::<Option<Vec<_>>>()
+                .is_some_and(|architectures| architectures.iter().all_unique())
+    }
+}
+
+struct NestedFile {
+    nested_installer_files: NestedInstallerFiles,
+    installers: Vec<Installer>,
+    command_alias: Option<String>,
+    has_adjacent_libraries: bool,
+}
+
+impl NestedFile {
+    fn is_portable(&self) -> bool {
+        self.installers
+            .iter()
+            .any(|installer| installer.r#type == Some(InstallerType::Portable))
+    }
 }
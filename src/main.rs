@@ -6,34 +6,48 @@ use tracing::{Level, metadata::LevelFilter};
 use tracing_indicatif::IndicatifLayer;
 use tracing_subscriber::{filter, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::commands::{
-    analyse::Analyse,
-    cleanup::Cleanup,
-    complete::Complete,
-    list_versions::ListVersions,
-    new_version::NewVersion,
-    remove_dead_versions::RemoveDeadVersions,
-    remove_version::RemoveVersion,
-    show_version::ShowVersion,
-    submit::Submit,
-    sync_fork::SyncFork,
-    token::commands::{TokenArgs, TokenCommands},
-    update_version::UpdateVersion,
+use crate::{
+    commands::{
+        analyse::Analyse,
+        cache::commands::{CacheArgs, CacheCommands},
+        check_urls::CheckUrls,
+        cleanup::Cleanup,
+        complete::Complete,
+        list_versions::ListVersions,
+        new_version::NewVersion,
+        remove_dead_versions::RemoveDeadVersions,
+        remove_version::RemoveVersion,
+        show_version::ShowVersion,
+        submit::Submit,
+        sync_fork::SyncFork,
+        token::commands::{TokenArgs, TokenCommands},
+        update_version::UpdateVersion,
+    },
+    github::{api::GitHubApiArgs, upstream::UpstreamArgs},
+    network::NetworkArgs,
 };
 
+mod checksums;
 mod commands;
 mod credential;
+mod download_cache;
 mod download_file;
 mod editor;
 mod file_analyser;
+mod forge;
 mod github;
+mod hash_drift;
 mod hyperlink;
 mod installers;
 mod manifests;
 mod match_installers;
+mod network;
+mod payload;
 mod prompts;
+mod repository;
 mod traits;
 mod update_state;
+mod vanity_url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -58,7 +72,12 @@ async fn main() -> Result<()> {
         )
         .init();
 
-    match Cli::parse().command {
+    let cli = Cli::parse();
+    cli.network.install()?;
+    cli.github_api.install()?;
+    cli.upstream.install();
+
+    match cli.command {
         Commands::New(new_version) => new_version.run().await,
         Commands::Update(update_version) => update_version.run().await,
         Commands::Cleanup(cleanup) => cleanup.run().await,
@@ -67,6 +86,10 @@ async fn main() -> Result<()> {
             TokenCommands::Remove(remove_token) => remove_token.run(),
             TokenCommands::Update(update_token) => update_token.run().await,
         },
+        Commands::Cache(cache_args) => match cache_args.command {
+            CacheCommands::List(list_cache) => list_cache.run(),
+            CacheCommands::Prune(prune_cache) => prune_cache.run(),
+        },
         Commands::ListVersions(list_versions) => list_versions.run().await,
         Commands::Show(show_version) => show_version.run().await,
         Commands::SyncFork(sync_fork) => sync_fork.run().await,
@@ -76,6 +99,7 @@ async fn main() -> Result<()> {
         }
         Commands::Analyse(analyse) => analyse.run(),
         Commands::RemoveDeadVersions(remove_dead_versions) => remove_dead_versions.run().await,
+        Commands::CheckUrls(check_urls) => check_urls.run().await,
         Commands::Submit(submit) => submit.run().await,
     }
 }
@@ -85,6 +109,12 @@ async fn main() -> Result<()> {
 struct Cli {
     #[arg(short = 'v', short_alias = 'V', long, action = clap::builder::ArgAction::Version)]
     version: (),
+    #[command(flatten)]
+    network: NetworkArgs,
+    #[command(flatten)]
+    github_api: GitHubApiArgs,
+    #[command(flatten)]
+    upstream: UpstreamArgs,
     #[command(subcommand)]
     command: Commands,
 }
@@ -96,11 +126,13 @@ enum Commands {
     Remove(RemoveVersion),
     Cleanup(Cleanup),
     Token(TokenArgs),
+    Cache(CacheArgs),
     ListVersions(ListVersions),
     Show(ShowVersion),
     SyncFork(SyncFork),
     Complete(Complete),
     Analyse(Analyse),
     RemoveDeadVersions(RemoveDeadVersions),
+    CheckUrls(CheckUrls),
     Submit(Submit),
 }
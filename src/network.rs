@@ -0,0 +1,89 @@
+use std::{fs, num::NonZeroU8, sync::OnceLock};
+
+use camino::Utf8PathBuf;
+use clap::Args;
+use color_eyre::eyre::{Result, WrapErr};
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use url::Url;
+
+use crate::credential::get_default_headers;
+
+static NETWORK_CONFIG: OnceLock<NetworkConfig> = OnceLock::new();
+
+/// Network options that apply to every request Komac makes.
+///
+/// Without `--proxy`, the `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` environment
+/// variables are used.
+#[derive(Args)]
+pub struct NetworkArgs {
+    /// 所有请求使用的代理，覆盖 HTTPS_PROXY 环境变量
+    #[arg(long, global = true, env = "KOMAC_PROXY", value_hint = clap::ValueHint::Url)]
+    proxy: Option<Url>,
+
+    /// 额外信任的 PEM 格式根证书，用于拦截 TLS 的企业代理
+    #[arg(long, global = true, env = "KOMAC_CA_BUNDLE", value_hint = clap::ValueHint::FilePath)]
+    ca_bundle: Option<Utf8PathBuf>,
+
+    /// 同时从同一主机下载的安装程序的最大数量
+    #[arg(long, global = true, env = "KOMAC_MAX_CONNECTIONS_PER_HOST")]
+    max_connections_per_host: Option<NonZeroU8>,
+}
+
+struct NetworkConfig {
+    proxy: Option<Proxy>,
+    root_certificates: Vec<Certificate>,
+    max_connections_per_host: Option<NonZeroU8>,
+}
+
+impl NetworkArgs {
+    /// Validates the options and makes them apply to every client created afterwards.
+    pub fn install(self) -> Result<()> {
+        let proxy = self
+            .proxy
+            .map(|proxy| {
+                Proxy::all(proxy.as_str()).map(|proxy| proxy.no_proxy(NoProxy::from_env()))
+            })
+            .transpose()?;
+
+        let root_certificates = match self.ca_bundle {
+            Some(ca_bundle) => Certificate::from_pem_bundle(
+                &fs::read(&ca_bundle).wrap_err_with(|| format!("无法读取 {ca_bundle}"))?,
+            )
+            .wrap_err_with(|| format!("无法解析 {ca_bundle} 中的证书"))?,
+            None => Vec::new(),
+        };
+
+        NETWORK_CONFIG.get_or_init(|| NetworkConfig {
+            proxy,
+            root_certificates,
+            max_connections_per_host: self.max_connections_per_host,
+        });
+
+        Ok(())
+    }
+}
+
+/// Creates a client builder with Komac's default headers and the configured network options.
+pub fn client_builder(github_token: Option<&str>) -> ClientBuilder {
+    let mut builder = Client::builder().default_headers(get_default_headers(github_token));
+    if let Some(config) = NETWORK_CONFIG.get() {
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &config.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+    }
+    builder
+}
+
+/// Creates a client with Komac's default headers and the configured network options.
+pub fn client(github_token: Option<&str>) -> reqwest::Result<Client> {
+    client_builder(github_token).build()
+}
+
+pub fn max_connections_per_host() -> Option<NonZeroU8> {
+    NETWORK_CONFIG
+        .get()
+        .and_then(|config| config.max_connections_per_host)
+}
@@ -0,0 +1,192 @@
+use std::{fs::File, io::SeekFrom, mem};
+
+use color_eyre::eyre::Result;
+use memmap2::Mmap;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tracing::debug;
+
+/// The number of bytes needed to recognise a format by its signature
+const SNIFF_LENGTH: usize = 8;
+
+/// Formats that are not recognised are kept in memory up to this size, as they are usually small
+/// text files such as App Installer files
+const MAX_IN_MEMORY_SIZE: usize = 16 << 20;
+
+const PE_SIGNATURE: &[u8] = b"MZ";
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+const COMPOUND_FILE_SIGNATURE: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+/// Returns whether a file starting with the given bytes is a format that can only be analysed with
+/// random access to the whole file.
+///
+/// Executables have their installer data appended after the PE image, ZIPs (including MSIX) have
+/// their central directory at the end and MSIs are compound files made of scattered sectors. This
+/// covers every installer format, so installers are still written to disk in full; only other files
+/// such as App Installer files are kept in memory.
+fn needs_random_access(signature: &[u8]) -> bool {
+    [PE_SIGNATURE, ZIP_SIGNATURE, COMPOUND_FILE_SIGNATURE]
+        .iter()
+        .any(|magic| signature.starts_with(magic))
+}
+
+/// The contents of a downloaded or local file, once it has been hashed.
+pub enum Payload {
+    /// A file on disk that is memory mapped for analysis
+    Mapped {
+        // The mapped file is kept open alongside the map as it is an unnamed temporary file that
+        // is deleted once closed
+        _file: File,
+        mmap: Mmap,
+    },
+    /// A small file that was kept in memory instead of written to disk
+    Buffered(Vec<u8>),
+    /// A file that was larger than the analysis threshold, so it was only hashed
+    Skipped,
+}
+
+impl Payload {
+    pub fn map(file: File) -> Result<Self> {
+        let mmap = unsafe { Mmap::map(&file) }?;
+        Ok(Self::Mapped { _file: file, mmap })
+    }
+
+    /// Returns the contents of the file, or `None` if the file was too large to analyse.
+    pub fn data(&self) -> Option<&[u8]> {
+        match self {
+            Self::Mapped { mmap, .. } => Some(mmap.as_ref()),
+            Self::Buffered(data) => Some(data.as_slice()),
+            Self::Skipped => None,
+        }
+    }
+}
+
+/// Where the body of a download is written as it streams in.
+///
+/// Unless the file is going to the download cache, the first bytes are held back until the format
+/// can be recognised. Formats that need random access, which includes every installer format, are
+/// written to a temporary file, while anything else is kept in memory. Disk use for large installers
+/// is only avoided by discarding files over the analysis threshold.
+pub enum Sink {
+    /// Not enough of the body has arrived to recognise its format
+    Sniffing(Vec<u8>),
+    Disk(tokio::fs::File),
+    Memory(Vec<u8>),
+    /// The file is too large to analyse, so the body is only hashed
+    Discard,
+}
+
+impl Sink {
+    pub fn disk(file: File) -> Self {
+        Self::Disk(tokio::fs::File::from_std(file))
+    }
+
+    pub async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        match self {
+            Self::Sniffing(buffer) => {
+                buffer.extend_from_slice(chunk);
+                if buffer.len() >= SNIFF_LENGTH {
+                    let buffer = mem::take(buffer);
+                    if needs_random_access(&buffer) {
+                        debug!("Writing download to disk as its format needs random access");
+                        *self = Self::spill(&buffer).await?;
+                    } else {
+                        *self = Self::Memory(buffer);
+                    }
+                }
+            }
+            Self::Disk(file) => file.write_all(chunk).await?,
+            Self::Memory(buffer) => {
+                buffer.extend_from_slice(chunk);
+                if buffer.len() > MAX_IN_MEMORY_SIZE {
+                    debug!("Writing download to disk as it is too large to keep in memory");
+                    *self = Self::spill(buffer).await?;
+                }
+            }
+            Self::Discard => {}
+        }
+        Ok(())
+    }
+
+    async fn spill(buffer: &[u8]) -> Result<Self> {
+        let mut file = tokio::fs::File::from_std(tempfile::tempfile()?);
+        file.write_all(buffer).await?;
+        Ok(Self::Disk(file))
+    }
+
+    /// Discards everything written so far, for when a download has to start over.
+    pub async fn reset(&mut self) -> Result<()> {
+        match self {
+            Self::Sniffing(buffer) | Self::Memory(buffer) => buffer.clear(),
+            Self::Disk(file) => {
+                file.set_len(0).await?;
+                file.seek(SeekFrom::Start(0)).await?;
+            }
+            Self::Discard => {}
+        }
+        Ok(())
+    }
+
+    pub async fn finish(self) -> Result<Payload> {
+        match self {
+            Self::Sniffing(buffer) | Self::Memory(buffer) => Ok(Payload::Buffered(buffer)),
+            Self::Disk(mut file) => {
+                file.flush().await?;
+                file.sync_all().await?;
+                Payload::map(file.into_std().await)
+            }
+            Self::Discard => Ok(Payload::Skipped),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::payload::{Payload, Sink, needs_random_access};
+
+    #[rstest]
+    #[case(b"MZ\x90\x00\x03\x00\x00\x00", true)]
+    #[case(b"PK\x03\x04\x14\x00\x00\x00", true)]
+    #[case(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1", true)]
+    #[case(b"<?xml version=\"1.0\"?>", false)]
+    #[case(b"", false)]
+    fn random_access(#[case] signature: &[u8], #[case] expected: bool) {
+        assert_eq!(needs_random_access(signature), expected);
+    }
+
+    #[tokio::test]
+    async fn text_is_kept_in_memory() {
+        let mut sink = Sink::Sniffing(Vec::new());
+        sink.write(b"<?xml ").await.unwrap();
+        sink.write(b"version=\"1.0\"?>").await.unwrap();
+        let payload = sink.finish().await.unwrap();
+        assert!(matches!(payload, Payload::Buffered(_)));
+        assert_eq!(payload.data(), Some(&b"<?xml version=\"1.0\"?>"[..]));
+    }
+
+    #[tokio::test]
+    async fn executable_is_written_to_disk() {
+        let mut sink = Sink::Sniffing(Vec::new());
+        sink.write(b"MZ").await.unwrap();
+        sink.write(b"\x90\x00\x03\x00\x00\x00 rest of the image")
+            .await
+            .unwrap();
+        let payload = sink.finish().await.unwrap();
+        assert!(matches!(payload, Payload::Mapped { .. }));
+        assert_eq!(
+            payload.data(),
+            Some(&b"MZ\x90\x00\x03\x00\x00\x00 rest of the image"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn reset_discards_written_data() {
+        let mut sink = Sink::Sniffing(Vec::new());
+        sink.write(b"MZ\x90\x00\x03\x00\x00\x00").await.unwrap();
+        sink.reset().await.unwrap();
+        sink.write(b"new").await.unwrap();
+        let payload = sink.finish().await.unwrap();
+        assert_eq!(payload.data(), Some(&b"new"[..]));
+    }
+}
@@ -2,7 +2,10 @@ use std::{fmt::Display, ops::BitOr, process};
 
 use bitflags::Flags;
 use inquire::{InquireError, MultiSelect, Select, error::InquireResult};
-use winget_types::{installer::UpgradeBehavior, shared::value::ValueName};
+use winget_types::{
+    installer::{InstallerType, UpgradeBehavior},
+    shared::value::ValueName,
+};
 
 pub mod list;
 pub mod text;
@@ -25,6 +28,35 @@ impl AllItems for UpgradeBehavior {
     }
 }
 
+impl AllItems for InstallerType {
+    type Item = Self;
+
+    fn all() -> impl IntoIterator<Item = Self::Item> {
+        [
+            Self::Item::Exe,
+            Self::Item::Inno,
+            Self::Item::Nullsoft,
+            Self::Item::Wix,
+            Self::Item::Burn,
+            Self::Item::Msi,
+            Self::Item::Msix,
+            Self::Item::Appx,
+            Self::Item::Zip,
+            Self::Item::Portable,
+        ]
+    }
+}
+
+/// Asks for the type of an installer that could not be determined by analysing the file.
+pub fn installer_type_prompt(file_name: &str) -> InquireResult<InstallerType> {
+    Select::new(
+        &format!("{file_name} 的安装程序类型:"),
+        InstallerType::all().into_iter().collect(),
+    )
+    .prompt()
+    .map_err(handle_inquire_error)
+}
+
 pub fn radio_prompt<T>() -> InquireResult<T>
 where
     T: ValueName + AllItems<Item = T> + Display,
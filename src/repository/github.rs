@@ -0,0 +1,55 @@
+use std::collections::BTreeSet;
+
+use color_eyre::eyre::Result;
+use winget_types::shared::{PackageIdentifier, PackageVersion};
+
+use crate::{
+    github::github_client::GitHub,
+    manifests::Manifests,
+    repository::{ManifestRepository, Submission, Submitted},
+};
+
+impl ManifestRepository for GitHub {
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>> {
+        Ok(Self::get_versions(self, identifier).await?)
+    }
+
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests> {
+        Ok(Self::get_manifests(self, identifier, version).await?)
+    }
+
+    async fn submit(&self, submission: Submission<'_>) -> Result<Submitted> {
+        let Submission {
+            identifier,
+            version,
+            versions,
+            changes,
+            replace_version,
+            issue_resolves,
+            created_with,
+            created_with_url,
+        } = submission;
+
+        let pull_request_url = self
+            .add_version()
+            .identifier(identifier)
+            .version(version)
+            .maybe_versions(versions)
+            .changes(changes)
+            .maybe_replace_version(replace_version)
+            .maybe_issue_resolves(issue_resolves)
+            .maybe_created_with(created_with)
+            .maybe_created_with_url(created_with_url)
+            .send()
+            .await?;
+
+        Ok(Submitted::PullRequest(pull_request_url))
+    }
+}
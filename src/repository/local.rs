@@ -0,0 +1,354 @@
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use color_eyre::eyre::{Result, bail, eyre};
+use tempfile::TempDir;
+use tracing::debug;
+use winget_types::shared::{PackageIdentifier, PackageVersion};
+
+use crate::{
+    github::{
+        github_client::{GitHubFile, parse_manifests},
+        upstream::upstream,
+        utils::{get_branch_name, get_commit_title, get_package_path},
+    },
+    manifests::Manifests,
+    repository::{ManifestRepository, Submission, Submitted},
+    update_state::UpdateState,
+};
+
+const MANIFESTS: &str = "manifests";
+const ORIGIN: &str = "origin";
+
+/// A local clone of winget-pkgs.
+///
+/// Versions and manifests are read from the upstream revision and submissions are committed on
+/// top of it through a separate index, so the working tree and the currently checked out branch
+/// are never read or touched.
+pub struct LocalRepository {
+    path: Utf8PathBuf,
+    push: Option<String>,
+    /// The commit that the upstream branch pointed to when the repository was opened
+    base: String,
+}
+
+impl LocalRepository {
+    pub fn open(path: Utf8PathBuf, push: Option<String>) -> Result<Self> {
+        if !path.join(MANIFESTS).is_dir() {
+            bail!("{path} 不是 winget-pkgs 的克隆，没有找到 {MANIFESTS} 目录");
+        }
+        let mut repository = Self {
+            path,
+            push,
+            base: String::new(),
+        };
+        repository.base = repository.resolve_base()?;
+        Ok(repository)
+    }
+
+    /// Resolves the upstream branch to a commit.
+    ///
+    /// A configured branch is looked up on the origin remote first, falling back to any revision
+    /// git understands. Otherwise, the origin's default branch is used, as the checked out branch
+    /// may be a stale or unrelated one.
+    fn resolve_base(&self) -> Result<String> {
+        let candidates = match &upstream().branch {
+            Some(branch) => vec![format!("refs/remotes/{ORIGIN}/{branch}"), branch.clone()],
+            None => vec![format!("refs/remotes/{ORIGIN}/HEAD")],
+        };
+        let base = candidates.iter().find_map(|revision| {
+            let revision = format!("{revision}^{{commit}}");
+            self.git(&["rev-parse", "--verify", "--quiet", &revision], None, None)
+                .ok()
+        });
+        match (base, &upstream().branch) {
+            (Some(base), _) => Ok(base),
+            (None, Some(branch)) => bail!("在 {} 中没有找到上游分支 {branch}", self.path),
+            (None, None) => bail!(
+                "无法确定 {} 的默认分支，请使用 --upstream-branch 指定，或运行 git remote set-head {ORIGIN} --auto",
+                self.path
+            ),
+        }
+    }
+
+    /// Lists the paths of all files under a directory at the upstream revision, relative to it.
+    fn list_files(&self, directory: &str) -> Result<Vec<String>> {
+        let output = self.git(
+            &[
+                "ls-tree",
+                "-r",
+                "-z",
+                "--name-only",
+                &self.base,
+                "--",
+                &format!("{directory}/"),
+            ],
+            None,
+            None,
+        )?;
+        Ok(output
+            .split('\0')
+            .filter_map(|path| path.strip_prefix(directory)?.strip_prefix('/'))
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Runs git in the repository, returning its trimmed standard output.
+    ///
+    /// Commands that stage changes are given their own index so that the working tree's index is
+    /// left alone.
+    fn git(&self, args: &[&str], index: Option<&Utf8Path>, stdin: Option<&str>) -> Result<String> {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.path).args(args);
+        if let Some(index) = index {
+            command.env("GIT_INDEX_FILE", index);
+        }
+        command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        debug!(?command);
+
+        let mut child = command
+            .spawn()
+            .map_err(|error| eyre!("无法运行 git: {error}"))?;
+        if let Some(input) = stdin {
+            child.stdin.take().unwrap().write_all(input.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "git 运行失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+    }
+}
+
+impl ManifestRepository for LocalRepository {
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>> {
+        let package_path = get_package_path(identifier, None, None);
+        let files = self.list_files(&package_path)?;
+        if files.is_empty() {
+            bail!("{identifier} 在 {} 中不存在", self.path);
+        }
+
+        // Version directories only contain manifests, whereas a directory containing other
+        // directories belongs to a package whose identifier extends this one
+        let mut directories = BTreeSet::new();
+        let mut nested = BTreeSet::new();
+        for file in &files {
+            let mut components = file.split('/');
+            let (Some(directory), Some(_)) = (components.next(), components.next()) else {
+                continue;
+            };
+            if components.next().is_some() {
+                nested.insert(directory);
+            } else {
+                directories.insert(directory);
+            }
+        }
+
+        let versions = directories
+            .difference(&nested)
+            .filter_map(|directory| PackageVersion::from_str(directory).ok())
+            .collect::<BTreeSet<_>>();
+
+        if versions.is_empty() {
+            bail!("在 {package_path} 中没有找到有效的文件");
+        }
+        Ok(versions)
+    }
+
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests> {
+        let full_package_path = get_package_path(identifier, Some(version), None);
+        let content = self
+            .list_files(&full_package_path)?
+            .into_iter()
+            .filter(|name| !name.contains('/'))
+            .map(|name| {
+                let text = self.git(
+                    &["show", &format!("{}:{full_package_path}/{name}", self.base)],
+                    None,
+                    None,
+                )?;
+                Ok(GitHubFile { name, text })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(parse_manifests(content, identifier, &full_package_path)?)
+    }
+
+    async fn submit(&self, submission: Submission<'_>) -> Result<Submitted> {
+        let Submission {
+            identifier,
+            version,
+            versions,
+            changes,
+            replace_version,
+            ..
+        } = submission;
+
+        // A throwaway index lets the commit be built without touching the working tree
+        let index_directory = TempDir::new()?;
+        let index_path = Utf8PathBuf::try_from(index_directory.path().join("index"))?;
+        let index = Some(index_path.as_path());
+        self.git(&["read-tree", &self.base], index, None)?;
+
+        if replace_version.is_some() {
+            let replaced_path = get_package_path(identifier, replace_version, None);
+            self.git(
+                &[
+                    "rm",
+                    "--cached",
+                    "-r",
+                    "-q",
+                    "--ignore-unmatch",
+                    "--",
+                    &replaced_path,
+                ],
+                index,
+                None,
+            )?;
+        }
+
+        for (path, content) in &changes {
+            let object = self.git(&["hash-object", "-w", "--stdin"], index, Some(content))?;
+            self.git(
+                &[
+                    "update-index",
+                    "--add",
+                    "--cacheinfo",
+                    &format!("100644,{object},{path}"),
+                ],
+                index,
+                None,
+            )?;
+        }
+
+        let tree = self.git(&["write-tree"], index, None)?;
+        let commit_title =
+            get_commit_title(identifier, version, &UpdateState::get(version, versions));
+        let commit = self.git(
+            &["commit-tree", &tree, "-p", &self.base, "-m", &commit_title],
+            index,
+            None,
+        )?;
+
+        let branch_name = get_branch_name(identifier, version);
+        self.git(
+            &["update-ref", &format!("refs/heads/{branch_name}"), &commit],
+            index,
+            None,
+        )?;
+
+        if let Some(remote) = &self.push {
+            self.git(&["push", "--quiet", remote, &branch_name], index, None)?;
+        }
+
+        Ok(Submitted::Branch {
+            name: branch_name,
+            remote: self.push.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, fs, process::Command};
+
+    use camino::{Utf8Path, Utf8PathBuf};
+    use tempfile::TempDir;
+    use winget_types::shared::{PackageIdentifier, PackageVersion};
+
+    use crate::repository::{ManifestRepository, local::LocalRepository};
+
+    fn git(root: &Utf8Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args([
+                "-c",
+                "user.name=Komac",
+                "-c",
+                "user.email=komac@example.com",
+            ])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[tokio::test]
+    async fn versions_exclude_nested_packages() {
+        let directory = TempDir::new().unwrap();
+        let root = Utf8PathBuf::try_from(directory.path().to_path_buf()).unwrap();
+        let package = root.join("manifests/m/Microsoft/Edge");
+        for version in ["1.0.0", "2.0.0", "Beta/3.0.0"] {
+            fs::create_dir_all(package.join(version)).unwrap();
+            fs::write(package.join(version).join("manifest.yaml"), "").unwrap();
+        }
+        git(&root, &["init", "--quiet"]);
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "--quiet", "-m", "Initial commit"]);
+        git(&root, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        git(
+            &root,
+            &[
+                "symbolic-ref",
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/main",
+            ],
+        );
+
+        // Versions that only exist in the working tree are not part of the upstream branch
+        fs::create_dir_all(package.join("4.0.0")).unwrap();
+        fs::write(package.join("4.0.0/manifest.yaml"), "").unwrap();
+
+        let repository = LocalRepository::open(root, None).unwrap();
+        let versions = repository
+            .get_versions(&"Microsoft.Edge".parse::<PackageIdentifier>().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            versions,
+            BTreeSet::from([
+                "1.0.0".parse::<PackageVersion>().unwrap(),
+                "2.0.0".parse::<PackageVersion>().unwrap()
+            ])
+        );
+    }
+
+    #[test]
+    fn no_default_branch() {
+        let directory = TempDir::new().unwrap();
+        let root = Utf8PathBuf::try_from(directory.path().to_path_buf()).unwrap();
+        fs::create_dir_all(root.join("manifests")).unwrap();
+        git(&root, &["init", "--quiet"]);
+        assert!(LocalRepository::open(root, None).is_err());
+    }
+
+    #[test]
+    fn not_a_clone() {
+        let directory = TempDir::new().unwrap();
+        let root = Utf8PathBuf::try_from(directory.path().to_path_buf()).unwrap();
+        assert!(LocalRepository::open(root, None).is_err());
+    }
+}
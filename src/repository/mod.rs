@@ -0,0 +1,174 @@
+mod github;
+mod local;
+
+use std::{collections::BTreeSet, num::NonZeroU32};
+
+use anstream::println;
+use camino::Utf8PathBuf;
+use clap::Args;
+use color_eyre::eyre::Result;
+use owo_colors::OwoColorize;
+use url::Url;
+use winget_types::shared::{PackageIdentifier, PackageVersion, url::DecodedUrl};
+
+pub use crate::repository::local::LocalRepository;
+use crate::{
+    credential::handle_token,
+    github::{github_client::GitHub, upstream::upstream},
+    manifests::Manifests,
+};
+
+/// A new version of a package to add to a repository.
+pub struct Submission<'a> {
+    pub identifier: &'a PackageIdentifier,
+    pub version: &'a PackageVersion,
+    pub versions: Option<&'a BTreeSet<PackageVersion>>,
+    pub changes: Vec<(String, String)>,
+    pub replace_version: Option<&'a PackageVersion>,
+    pub issue_resolves: Option<Vec<NonZeroU32>>,
+    pub created_with: Option<String>,
+    pub created_with_url: Option<DecodedUrl>,
+}
+
+/// Where a submission ended up.
+pub enum Submitted {
+    PullRequest(Url),
+    /// An existing pull request that was updated with a new commit
+    AmendedPullRequest(Url),
+    /// A branch in a local repository, with the remote it was pushed to if any
+    Branch {
+        name: String,
+        remote: Option<String>,
+    },
+}
+
+impl Submitted {
+    /// Tells the user where the submission can be found, opening the pull request if asked to.
+    pub fn report(&self, open_pr: bool) -> Result<()> {
+        match self {
+            Self::PullRequest(url) => {
+                println!("{} 创建了一个拉取请求到 {}", "成功".green(), upstream());
+                println!("{}", url.as_str());
+                if open_pr {
+                    open::that(url.as_str())?;
+                }
+            }
+            Self::AmendedPullRequest(url) => {
+                println!("{} 更新了现有的拉取请求", "成功".green());
+                println!("{}", url.as_str());
+                if open_pr {
+                    open::that(url.as_str())?;
+                }
+            }
+            Self::Branch { name, remote: None } => {
+                println!("{} 在本地仓库中创建了分支 {name}", "成功".green());
+            }
+            Self::Branch {
+                name,
+                remote: Some(remote),
+            } => {
+                println!(
+                    "{} 在本地仓库中创建了分支 {name} 并推送到 {remote}",
+                    "成功".green()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A store of manifests that versions can be read from and submitted to.
+pub trait ManifestRepository {
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>>;
+
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests>;
+
+    async fn submit(&self, submission: Submission<'_>) -> Result<Submitted>;
+}
+
+#[derive(Args)]
+pub struct RepositoryArgs {
+    /// 使用本地的 winget-pkgs 克隆代替 GitHub API，无需联网即可读取和提交清单
+    #[arg(long, env = "KOMAC_LOCAL_REPOSITORY", value_hint = clap::ValueHint::DirPath)]
+    local_repository: Option<Utf8PathBuf>,
+
+    /// 在本地克隆中提交后，将分支推送到此远程仓库
+    #[arg(
+        long,
+        requires = "local_repository",
+        num_args = 0..=1,
+        default_missing_value = "origin",
+        value_name = "REMOTE"
+    )]
+    push: Option<String>,
+}
+
+impl RepositoryArgs {
+    /// Opens the local repository if one was given, and otherwise authenticates with GitHub.
+    pub async fn open(&self, token: Option<&str>) -> Result<Repository> {
+        match &self.local_repository {
+            Some(path) => Ok(Repository::Local(LocalRepository::open(
+                path.clone(),
+                self.push.clone(),
+            )?)),
+            None => {
+                let token = handle_token(token).await?;
+                Ok(Repository::GitHub(GitHub::new(&token)?))
+            }
+        }
+    }
+}
+
+pub enum Repository {
+    GitHub(GitHub),
+    Local(LocalRepository),
+}
+
+impl Repository {
+    /// Returns the GitHub client, for features that are only available through the GitHub API.
+    pub const fn github(&self) -> Option<&GitHub> {
+        match self {
+            Self::GitHub(github) => Some(github),
+            Self::Local(_) => None,
+        }
+    }
+}
+
+impl ManifestRepository for Repository {
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>> {
+        match self {
+            Self::GitHub(github) => ManifestRepository::get_versions(github, identifier).await,
+            Self::Local(local) => local.get_versions(identifier).await,
+        }
+    }
+
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests> {
+        match self {
+            Self::GitHub(github) => {
+                ManifestRepository::get_manifests(github, identifier, version).await
+            }
+            Self::Local(local) => local.get_manifests(identifier, version).await,
+        }
+    }
+
+    async fn submit(&self, submission: Submission<'_>) -> Result<Submitted> {
+        match self {
+            Self::GitHub(github) => github.submit(submission).await,
+            Self::Local(local) => local.submit(submission).await,
+        }
+    }
+}
@@ -1,7 +1,13 @@
-use reqwest::{Client, ClientBuilder, Response, redirect::Policy};
+use reqwest::{
+    Client, Response,
+    header::{LOCATION, RANGE},
+    redirect::Policy,
+};
+use tracing::{debug, info};
+use url::Url;
 use winget_types::shared::url::DecodedUrl;
 
-use crate::github::github_client::GITHUB_HOST;
+use crate::{network, vanity_url::find_resolver};
 
 pub trait UpgradeToHttps {
     async fn upgrade_to_https(&mut self, client: &Client);
@@ -27,44 +33,94 @@ impl UpgradeToHttps for DecodedUrl {
     }
 }
 
-pub trait ConvertGitHubLatestToVersioned {
-    /// Converts a vanity GitHub URL that always points to the latest release to its versioned URL by
-    /// following the redirect by one hop.
+pub trait ResolveVanityUrl {
+    /// Converts a vanity URL that always points to the latest release to its versioned URL by
+    /// following redirects until one is reached that the matching resolver recognises as
+    /// versioned.
     ///
     /// For example, github.com/owner/repo/releases/latest/download/file.exe to
     /// github.com/owner/repo/releases/download/v1.2.3/file.exe
-    async fn convert_github_latest_to_versioned(&mut self) -> reqwest::Result<()>;
+    ///
+    /// Returns [`Resolution::Unresolved`] if the URL is a vanity URL whose versioned URL could
+    /// not be found.
+    async fn resolve_vanity_url(&mut self) -> reqwest::Result<Resolution>;
+}
+
+/// The outcome of resolving a URL that may always point at the latest release.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Resolution {
+    /// The URL is versioned, either as it was given or after being replaced
+    Versioned,
+    /// The URL looks like a vanity URL but no versioned URL was redirected to
+    Unresolved {
+        /// Whether the host always redirects such URLs, rather than possibly serving the file
+        /// directly
+        always_redirects: bool,
+    },
 }
 
-impl ConvertGitHubLatestToVersioned for DecodedUrl {
-    async fn convert_github_latest_to_versioned(&mut self) -> reqwest::Result<()> {
-        const LATEST: &str = "latest";
-        const DOWNLOAD: &str = "download";
-        const MAX_HOPS: u8 = 2;
+impl ResolveVanityUrl for DecodedUrl {
+    async fn resolve_vanity_url(&mut self) -> reqwest::Result<Resolution> {
+        const MAX_HOPS: u8 = 5;
 
-        if self.host_str() != Some(GITHUB_HOST) {
-            return Ok(());
-        }
+        let Some(resolver) = find_resolver(self) else {
+            return Ok(Resolution::Versioned);
+        };
 
-        if let Some(mut segments) = self.path_segments() {
-            // If the 4th and 5th segments are 'latest' and 'download', it's a vanity URL
-            if segments.nth(3) == Some(LATEST) && segments.next() == Some(DOWNLOAD) {
-                // Create a client that will redirect only once
-                let limited_redirect_client = ClientBuilder::new()
-                    .redirect(Policy::limited(MAX_HOPS as usize))
-                    .build()?;
+        // Follow each redirect manually so that the versioned URL can be taken as soon as it is
+        // reached, rather than the CDN or mirror URL that it in turn redirects to
+        let no_redirect_client = network::client_builder(None)
+            .redirect(Policy::none())
+            .build()?;
 
-                // If there was a redirect error because max hops were reached, as intended, set the
-                // original vanity URL to the redirected versioned URL
-                if let Err(error) = limited_redirect_client.head(self.as_str()).send().await {
-                    if error.is_redirect() {
-                        if let Some(final_url) = error.url() {
-                            **self = final_url.clone();
-                        }
-                    }
-                };
+        let mut current_url = Url::clone(self);
+        for _ in 0..MAX_HOPS {
+            let response = probe(&no_redirect_client, &current_url).await?;
+            let Some(location) = response
+                .status()
+                .is_redirection()
+                .then(|| response.headers().get(LOCATION))
+                .flatten()
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| current_url.join(location).ok())
+            else {
+                break;
+            };
+
+            if resolver.is_versioned(&location) {
+                let versioned_url = resolver.normalize(location);
+                debug!(resolver = resolver.name(), %versioned_url);
+                info!(
+                    "{} 始终指向最新版本，已替换为 {versioned_url}",
+                    self.as_str()
+                );
+                **self = versioned_url;
+                return Ok(Resolution::Versioned);
             }
+            current_url = location;
+        }
+
+        Ok(Resolution::Unresolved {
+            always_redirects: resolver.always_redirects(),
+        })
+    }
+}
+
+/// Requests a URL without downloading its body, falling back to a GET for its first byte when the
+/// server rejects HEAD requests.
+async fn probe(client: &Client, url: &Url) -> reqwest::Result<Response> {
+    match client.head(url.as_str()).send().await {
+        Ok(response)
+            if !(response.status().is_client_error() || response.status().is_server_error()) =>
+        {
+            Ok(response)
+        }
+        _ => {
+            client
+                .get(url.as_str())
+                .header(RANGE, "bytes=0-0")
+                .send()
+                .await
         }
-        Ok(())
     }
 }
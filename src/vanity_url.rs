@@ -0,0 +1,322 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use url::Url;
+
+use crate::github::github_client::GITHUB_HOST;
+
+/// Resolves a vanity URL, which always points at the latest release, to the versioned URL it
+/// currently redirects to.
+///
+/// Publishing a vanity URL would mean the manifest's hash silently stops matching as soon as a new
+/// version is released, so every URL is resolved before it is downloaded and hashed.
+pub trait VanityUrlResolver: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Returns whether the URL always points at the latest release.
+    fn is_vanity(&self, url: &Url) -> bool;
+
+    /// Returns whether a URL redirected to is the versioned URL of a specific release.
+    fn is_versioned(&self, url: &Url) -> bool;
+
+    /// Returns whether the host always redirects vanity URLs to a versioned URL, so that failing
+    /// to find one means something went wrong rather than the file being served directly.
+    fn always_redirects(&self) -> bool {
+        false
+    }
+
+    /// Converts the versioned URL to the form that should be published, such as by removing
+    /// details of the mirror that was redirected to.
+    fn normalize(&self, url: Url) -> Url {
+        url
+    }
+}
+
+/// The resolvers in the order they are tried, from the most specific to the most general.
+pub static RESOLVERS: [&dyn VanityUrlResolver; 5] = [
+    &GitHubLatest,
+    &GitLabPermalink,
+    &SourceForgeLatest,
+    &GiteaLatest,
+    &RedirectingCdn,
+];
+
+const LATEST: &str = "latest";
+const DOWNLOAD: &str = "download";
+const RELEASES: &str = "releases";
+
+/// Returns the first resolver that recognises the URL as a vanity URL.
+pub fn find_resolver(url: &Url) -> Option<&'static dyn VanityUrlResolver> {
+    RESOLVERS
+        .iter()
+        .find(|resolver| resolver.is_vanity(url))
+        .copied()
+}
+
+fn segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// `github.com/owner/repo/releases/latest/download/file.exe` to
+/// `github.com/owner/repo/releases/download/v1.2.3/file.exe`
+struct GitHubLatest;
+
+impl VanityUrlResolver for GitHubLatest {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn is_vanity(&self, url: &Url) -> bool {
+        url.host_str() == Some(GITHUB_HOST)
+            && segments(url).get(2..5) == Some(&[RELEASES, LATEST, DOWNLOAD][..])
+    }
+
+    fn is_versioned(&self, url: &Url) -> bool {
+        url.host_str() == Some(GITHUB_HOST)
+            && segments(url).get(2..5).is_some_and(|segments| {
+                segments[..2] == [RELEASES, DOWNLOAD] && segments[2] != LATEST
+            })
+    }
+
+    fn always_redirects(&self) -> bool {
+        true
+    }
+}
+
+/// `gitlab.com/group/project/-/releases/permalink/latest/downloads/file.exe` to
+/// `gitlab.com/group/project/-/releases/v1.2.3/downloads/file.exe`
+///
+/// <https://docs.gitlab.com/user/project/releases/release_fields/#permanent-link-to-latest-release>
+struct GitLabPermalink;
+
+impl GitLabPermalink {
+    const PERMALINK: &'static str = "permalink";
+
+    /// Returns the segments following `/-/releases/` in a GitLab URL.
+    fn release_segments(url: &Url) -> Option<Vec<&str>> {
+        let segments = segments(url);
+        let index = segments
+            .windows(2)
+            .position(|window| window == ["-", RELEASES])?;
+        Some(segments[index + 2..].to_vec())
+    }
+}
+
+impl VanityUrlResolver for GitLabPermalink {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn is_vanity(&self, url: &Url) -> bool {
+        Self::release_segments(url)
+            .is_some_and(|segments| segments.starts_with(&[Self::PERMALINK, LATEST]))
+    }
+
+    fn is_versioned(&self, url: &Url) -> bool {
+        Self::release_segments(url)
+            .is_some_and(|segments| segments.first().is_some_and(|&tag| tag != Self::PERMALINK))
+    }
+
+    fn always_redirects(&self) -> bool {
+        true
+    }
+}
+
+/// `sourceforge.net/projects/project/files/latest/download` to
+/// `sourceforge.net/projects/project/files/1.2.3/file.exe/download`
+///
+/// SourceForge redirects to a file on `downloads.sourceforge.net` with the mirror to use in the
+/// query, which is converted back to the project's stable download link.
+struct SourceForgeLatest;
+
+impl SourceForgeLatest {
+    const HOSTS: [&'static str; 2] = ["sourceforge.net", "www.sourceforge.net"];
+    const DOWNLOADS_HOST: &'static str = "downloads.sourceforge.net";
+    const PROJECTS: &'static str = "projects";
+    const PROJECT: &'static str = "project";
+    const FILES: &'static str = "files";
+}
+
+impl VanityUrlResolver for SourceForgeLatest {
+    fn name(&self) -> &'static str {
+        "SourceForge"
+    }
+
+    fn is_vanity(&self, url: &Url) -> bool {
+        let segments = segments(url);
+        url.host_str()
+            .is_some_and(|host| Self::HOSTS.contains(&host))
+            && segments.first() == Some(&Self::PROJECTS)
+            && segments.ends_with(&[LATEST, DOWNLOAD])
+    }
+
+    fn is_versioned(&self, url: &Url) -> bool {
+        url.host_str() == Some(Self::DOWNLOADS_HOST)
+            && segments(url).first() == Some(&Self::PROJECT)
+    }
+
+    fn always_redirects(&self) -> bool {
+        true
+    }
+
+    fn normalize(&self, url: Url) -> Url {
+        let segments = segments(&url);
+        let Some((project, path)) = segments.get(1).zip(segments.get(2..)) else {
+            return url;
+        };
+        format!(
+            "https://{}/{}/{project}/{}/{}/{DOWNLOAD}",
+            Self::HOSTS[0],
+            Self::PROJECTS,
+            Self::FILES,
+            path.join("/")
+        )
+        .parse()
+        .unwrap_or(url)
+    }
+}
+
+/// `codeberg.org/owner/repo/releases/download/latest/file.exe` to
+/// `codeberg.org/owner/repo/releases/download/v1.2.3/file.exe`
+///
+/// This matches any Gitea or Forgejo instance, as they can be self-hosted on any domain.
+struct GiteaLatest;
+
+impl VanityUrlResolver for GiteaLatest {
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn is_vanity(&self, url: &Url) -> bool {
+        segments(url).get(2..5).is_some_and(|segments| {
+            segments == [RELEASES, DOWNLOAD, LATEST] || segments == [RELEASES, LATEST, DOWNLOAD]
+        })
+    }
+
+    fn is_versioned(&self, url: &Url) -> bool {
+        segments(url)
+            .get(2..5)
+            .is_some_and(|segments| segments[..2] == [RELEASES, DOWNLOAD] && segments[2] != LATEST)
+    }
+}
+
+/// Any other URL with a path segment such as `latest` that redirects to a URL containing a
+/// version number, like `example.com/download/latest/setup.exe` to
+/// `cdn.example.com/1.2.3/setup-1.2.3.exe`.
+struct RedirectingCdn;
+
+static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+\.\d+").unwrap());
+
+impl RedirectingCdn {
+    const VANITY_SEGMENTS: [&'static str; 3] = [LATEST, "current", "stable"];
+
+    /// Returns whether the URL has a segment such as `latest` that isn't followed by a version,
+    /// as `/stable/app-1.2.3.exe` is a specific version in a channel rather than a vanity URL.
+    fn has_vanity_segment(url: &Url) -> bool {
+        let segments = segments(url);
+        segments.iter().enumerate().any(|(index, segment)| {
+            Self::VANITY_SEGMENTS
+                .iter()
+                .any(|vanity| segment.eq_ignore_ascii_case(vanity))
+                && !segments[index + 1..]
+                    .iter()
+                    .any(|segment| VERSION_REGEX.is_match(segment))
+        })
+    }
+}
+
+impl VanityUrlResolver for RedirectingCdn {
+    fn name(&self) -> &'static str {
+        "CDN"
+    }
+
+    fn is_vanity(&self, url: &Url) -> bool {
+        Self::has_vanity_segment(url)
+    }
+
+    fn is_versioned(&self, url: &Url) -> bool {
+        !Self::has_vanity_segment(url) && VERSION_REGEX.is_match(url.path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use url::Url;
+
+    use crate::vanity_url::find_resolver;
+
+    #[rstest]
+    #[case(
+        "https://github.com/owner/repo/releases/latest/download/app.exe",
+        "GitHub",
+        "https://github.com/owner/repo/releases/download/v1.2.3/app.exe"
+    )]
+    #[case(
+        "https://gitlab.com/group/project/-/releases/permalink/latest/downloads/app.exe",
+        "GitLab",
+        "https://gitlab.com/group/project/-/releases/v1.2.3/downloads/app.exe"
+    )]
+    #[case(
+        "https://sourceforge.net/projects/example/files/latest/download",
+        "SourceForge",
+        "https://downloads.sourceforge.net/project/example/1.2.3/app.exe"
+    )]
+    #[case(
+        "https://codeberg.org/owner/repo/releases/download/latest/app.exe",
+        "Gitea",
+        "https://codeberg.org/owner/repo/releases/download/v1.2.3/app.exe"
+    )]
+    #[case(
+        "https://example.com/download/latest/app.exe",
+        "CDN",
+        "https://cdn.example.com/1.2.3/app-1.2.3.exe"
+    )]
+    fn resolver(#[case] vanity: &str, #[case] name: &str, #[case] versioned: &str) {
+        let resolver = find_resolver(&vanity.parse::<Url>().unwrap()).unwrap();
+        assert_eq!(resolver.name(), name);
+        assert!(resolver.is_versioned(&versioned.parse::<Url>().unwrap()));
+    }
+
+    #[rstest]
+    #[case("https://github.com/owner/repo/releases/download/v1.2.3/app.exe")]
+    #[case("https://example.com/1.2.3/app.exe")]
+    #[case("https://example.com/stable/app-1.2.3.exe")]
+    #[case("https://example.com/latest/1.2.3/app.exe")]
+    fn not_vanity(#[case] url: &str) {
+        assert!(find_resolver(&url.parse::<Url>().unwrap()).is_none());
+    }
+
+    #[rstest]
+    #[case("https://github.com/owner/repo/releases/latest/download/app.exe", true)]
+    #[case("https://sourceforge.net/projects/example/files/latest/download", true)]
+    #[case(
+        "https://codeberg.org/owner/repo/releases/download/latest/app.exe",
+        false
+    )]
+    #[case("https://example.com/download/latest/app.exe", false)]
+    fn always_redirects(#[case] url: &str, #[case] expected: bool) {
+        let resolver = find_resolver(&url.parse::<Url>().unwrap()).unwrap();
+        assert_eq!(resolver.always_redirects(), expected);
+    }
+
+    #[test]
+    fn sourceforge_normalize() {
+        let resolver = find_resolver(
+            &"https://sourceforge.net/projects/example/files/latest/download"
+                .parse::<Url>()
+                .unwrap(),
+        )
+        .unwrap();
+        let mirror =
+            "https://downloads.sourceforge.net/project/example/1.2.3/app.exe?ts=1&use_mirror=netix"
+                .parse::<Url>()
+                .unwrap();
+        assert_eq!(
+            resolver.normalize(mirror).as_str(),
+            "https://sourceforge.net/projects/example/files/1.2.3/app.exe/download"
+        );
+    }
+}